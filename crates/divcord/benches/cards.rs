@@ -23,6 +23,11 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             )
         })
     });
+
+    let index = divcord::DivcordIndex::new(&records, &poe_data);
+    c.bench_function("cards with prebuilt index", |b| {
+        b.iter(|| black_box(&index).cards_by_source_types(black_box(&all_source_types)))
+    });
 }
 
 fn configured_criterion() -> Criterion {
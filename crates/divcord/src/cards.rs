@@ -1,8 +1,6 @@
-use crate::{dropsource::predefined::PredefinedSource, Record, Source};
-use itertools::Either;
+use crate::{dropsource::predefined::PredefinedSource, index::DivcordIndex, Record, Source};
 use crate::poe_data::{act::Bossfight, mapbosses::MapBoss, maps::Map, PoeData};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, Hash, PartialEq)]
 pub enum VerificationStatus {
@@ -12,105 +10,55 @@ pub enum VerificationStatus {
     Verify,
     #[serde(rename = "atlas")]
     Atlas,
+    /// Found in game files, not confirmed by drops.
+    #[serde(rename = "datamined")]
+    Datamined,
+    /// Global drop card, that is eligible at the area level.
+    #[serde(rename = "global")]
+    Global,
 }
 
+/// Builds [`DivcordIndex`] for a single lookup, build the index once for many lookups.
 pub fn cards_by_source<'a>(
     source: &'a Source,
     records: &'a [Record],
     poe_data: &'a PoeData,
 ) -> Vec<CardBySource> {
-    let direct_cards = get_direct_cards_from_source(source, records, poe_data)
-        .collect::<HashSet<Direct>>()
-        .into_iter()
-        .map(CardBySource::Direct);
-    let transitive_cards = get_transitive_cards_from_source(source, records, poe_data)
-        .collect::<HashSet<Transitive>>()
-        .into_iter()
-        .map(CardBySource::Transitive);
-    direct_cards.chain(transitive_cards).collect()
+    DivcordIndex::new(records, poe_data).cards_by_source(source)
 }
 
+/// Builds [`DivcordIndex`] for a single lookup, build the index once for many lookups.
 pub fn cards_by_source_types(
     source_types: &[String],
     records: &[Record],
     poe_data: &PoeData,
 ) -> Vec<SourceAndCards> {
-    let mut hash_map: HashMap<Source, HashSet<CardBySource>> = HashMap::new();
-
-    // 1. Collect all direct drops
-    let mut process_source = |record: &Record, source: &Source, status: VerificationStatus| {
-        if !source_types.iter().any(|s| source._type() == *s) {
-            return;
-        }
-
-        let entry = hash_map.entry(source.clone()).or_default();
-        entry.insert(CardBySource::Direct(Direct {
-            // source: source.clone(),
-            card: record.card.clone(),
-            status,
-        }));
-    };
-    records.iter().for_each(|r| {
-        r.sources
-            .iter()
-            .for_each(|s| process_source(r, s, VerificationStatus::Done));
-        r.verify_sources
-            .iter()
-            .for_each(|v| process_source(r, v, VerificationStatus::Verify));
-    });
-
-    // 2. Maps: Atlas cards + transitive sources
-    if source_types.contains(&"Map".to_owned()) {
-        poe_data.maps.iter().for_each(|m| {
-            let m_as_source = Source::Map(m.name.clone());
-            let entry = hash_map.entry(m_as_source.clone()).or_default();
-
-            // a. Add atlas cards
-            m.atlas_cards.clone().into_iter().for_each(|c| {
-                entry.insert(CardBySource::Direct(Direct {
-                    card: c,
-                    status: VerificationStatus::Atlas,
-                }));
-            });
-
-            // b. From transitive sources (map bosses)
-            let set: HashSet<CardBySource> =
-                get_transitive_cards_from_source(&m_as_source, records, poe_data)
-                    .map(CardBySource::Transitive)
-                    .collect();
-
-            if !set.is_empty() {
-                entry.extend(set);
-            }
-        });
-    };
-
-    // 3. Acts transitive sources (boss-specific cards)
-    if source_types.contains(&"Act".to_owned()) {
-        poe_data.acts.iter().for_each(|a| {
-            if a.is_town {
-                return;
-            }
-
-            let act = Source::Act(a.id.clone());
+    DivcordIndex::new(records, poe_data).cards_by_source_types(source_types)
+}
 
-            let set: HashSet<CardBySource> =
-                get_transitive_cards_from_source(&act, records, poe_data)
-                    .map(CardBySource::Transitive)
-                    .collect();
+/// Like [`cards_by_source`], plus global drop cards eligible at the level of the map or act area.
+pub fn cards_by_source_with_global_drops(
+    source: &Source,
+    records: &[Record],
+    poe_data: &PoeData,
+) -> Vec<CardBySource> {
+    DivcordIndex::new(records, poe_data).cards_by_source_with_global_drops(source)
+}
 
-            if !set.is_empty() {
-                hash_map.entry(act).or_default().extend(set);
-            }
-        });
-    }
+/// Global drop cards, that can drop at the area level.
+pub fn global_drop_cards(area_level: u32, records: &[Record], poe_data: &PoeData) -> Vec<String> {
+    DivcordIndex::new(records, poe_data)
+        .global_drop_cards(area_level)
+        .map(str::to_owned)
+        .collect()
+}
 
-    hash_map
-        .into_iter()
-        .map(|(source, cards)| SourceAndCards {
-            source,
-            cards: Vec::from_iter(cards),
-        })
+/// Where the card drops: listed directly, via a boss of a map or act area, or on an atlas map node.
+/// Builds [`DivcordIndex`] for a single lookup, build the index once for many lookups.
+pub fn sources_by_card(card: &str, records: &[Record], poe_data: &PoeData) -> Vec<SourceByCard> {
+    DivcordIndex::new(records, poe_data)
+        .sources_by_card(card)
+        .cloned()
         .collect()
 }
 
@@ -175,6 +123,65 @@ impl CardBySource {
     // }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DirectSource {
+    pub source: Source,
+    pub status: VerificationStatus,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TransitiveSource {
+    /// Map or act area.
+    pub source: Source,
+    pub status: VerificationStatus,
+    /// Boss of the map or act area, that drops the card.
+    pub transitive: Source,
+}
+
+/// Source of a card, reverse of [`CardBySource`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SourceByCard {
+    Direct(DirectSource),
+    Transitive(TransitiveSource),
+}
+
+impl SourceByCard {
+    pub fn is_transitive(&self) -> bool {
+        match self {
+            SourceByCard::Direct(_) => false,
+            SourceByCard::Transitive(_) => true,
+        }
+    }
+
+    pub fn is_direct(&self) -> bool {
+        match self {
+            SourceByCard::Direct(_) => true,
+            SourceByCard::Transitive(_) => false,
+        }
+    }
+
+    /// Atlas map node, from the in-game atlas.
+    pub fn is_atlas(&self) -> bool {
+        self.status() == VerificationStatus::Atlas
+    }
+
+    pub fn status(&self) -> VerificationStatus {
+        match self {
+            SourceByCard::Direct(d) => d.status,
+            SourceByCard::Transitive(t) => t.status,
+        }
+    }
+
+    /// Direct source, or map or act area of the transitive one.
+    pub fn source(&self) -> &Source {
+        match self {
+            SourceByCard::Direct(d) => &d.source,
+            SourceByCard::Transitive(t) => &t.source,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct SourceAndCards {
     pub source: Source,
@@ -210,96 +217,35 @@ impl From<crate::poe_data::act::ActArea> for Source {
     }
 }
 
+/// Bosses of the map or act area, see [`DivcordIndex::transitive_sources`].
 pub fn transitive_sources(source: &Source, poe_data: &PoeData) -> Vec<Source> {
-    match source {
-        Source::Act(act) => poe_data
-            .act_area_id(act)
-            .map(|act_area| {
-                act_area
-                    .bossfights
-                    .iter()
-                    .map(|b| Source::from(b.to_owned()))
-                    .collect()
-            })
-            .unwrap_or_default(),
-        Source::Map(map) => poe_data
-            .bosses_of_map(map)
-            .into_iter()
-            .map(|b| Source::from(b.to_owned()))
-            .collect(),
-        _ => vec![],
-    }
+    DivcordIndex::new(&[], poe_data)
+        .transitive_sources(source)
+        .to_vec()
 }
 
-pub fn get_transitive_cards_from_source<'a>(
-    direct_source: &'a Source,
-    records: &'a [Record],
-    poe_data: &'a PoeData,
-) -> impl Iterator<Item = Transitive> + 'a {
-    transitive_sources(direct_source, poe_data)
+/// Builds [`DivcordIndex`] for a single lookup, see [`DivcordIndex::transitive_cards`].
+pub fn get_transitive_cards_from_source(
+    direct_source: &Source,
+    records: &[Record],
+    poe_data: &PoeData,
+) -> impl Iterator<Item = Transitive> {
+    DivcordIndex::new(records, poe_data)
+        .transitive_cards(direct_source)
         .into_iter()
-        .flat_map(move |transit| {
-            get_direct_cards_from_source(&transit, records, poe_data)
-                .collect::<Vec<_>>()
-                .into_iter()
-                .map(move |by_transit| Transitive {
-                    // source: direct_source.to_owned(),
-                    card: by_transit.card,
-                    status: by_transit.status,
-                    transitive: transit.clone(),
-                })
-        })
 }
 
-pub fn get_direct_cards_from_source<'a>(
-    direct_source: &'a Source,
-    records: &'a [Record],
-    poe_data: &'a PoeData,
-) -> impl Iterator<Item = Direct> + 'a {
-    let iterator_cards = records.iter().flat_map(move |record| {
-        let card = &record.card;
-        record
-            .sources
-            .iter()
-            .filter(move |&s| s == direct_source)
-            .map(move |_s| Direct {
-                // source: s.clone(),
-                card: card.clone(),
-                status: VerificationStatus::Done,
-            })
-            .chain(
-                record
-                    .verify_sources
-                    .iter()
-                    .filter(move |&s| s == direct_source)
-                    .map(move |_s| Direct {
-                        // source: s.clone(),
-                        card: card.clone(),
-                        status: VerificationStatus::Verify,
-                    }),
-            )
-    });
-
-    if let Source::Map(map) = &direct_source {
-        let atlas_cards = poe_data
-            .maps
-            .iter()
-            .find(|m| map.as_str() == m.name)
-            // Map is expected to exist any single time. Maybe will wrap in Result later.
-            // But no need for this.
-            .unwrap_or_else(|| panic!("Map {} does not exist in PoeData", map.as_str()))
-            .atlas_cards
-            .clone()
-            .into_iter()
-            .map(|c| Direct {
-                card: c,
-                status: VerificationStatus::Atlas,
-            });
-
-        return Either::Left(iterator_cards.chain(atlas_cards));
-    };
-
-    Either::Right(iterator_cards)
+/// Builds [`DivcordIndex`] for a single lookup, see [`DivcordIndex::direct_cards`].
+pub fn get_direct_cards_from_source(
+    direct_source: &Source,
+    records: &[Record],
+    poe_data: &PoeData,
+) -> impl Iterator<Item = Direct> {
+    DivcordIndex::new(records, poe_data)
+        .direct_cards(direct_source)
+        .cloned()
+        .collect::<Vec<_>>()
+        .into_iter()
 }
 
 impl From<Transitive> for CardBySource {
@@ -337,6 +283,8 @@ mod tests {
                 sources: vec![source.clone()],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
             Record {
                 id: 2,
@@ -348,6 +296,8 @@ mod tests {
                 sources: vec![],
                 notes: None,
                 verify_sources: vec![source.clone()],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
             Record {
                 id: 3,
@@ -359,19 +309,24 @@ mod tests {
                 sources: vec![],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![source.clone()],
+                hypothesis: None,
             },
         ];
 
         let result: Vec<_> =
             super::get_direct_cards_from_source(&source, &records, &PoeData::default()).collect();
 
-        assert_eq!(result.len(), 2);
+        assert_eq!(result.len(), 3);
         assert!(result
             .iter()
             .any(|d| d.card == "Card1" && d.status == VerificationStatus::Done));
         assert!(result
             .iter()
             .any(|d| d.card == "Card2" && d.status == VerificationStatus::Verify));
+        assert!(result
+            .iter()
+            .any(|d| d.card == "Card3" && d.status == VerificationStatus::Datamined));
     }
 
     #[test]
@@ -391,6 +346,8 @@ mod tests {
             sources: vec![boss_source.clone()],
             notes: None,
             verify_sources: vec![],
+            datamined_sources: vec![],
+            hypothesis: None,
         }];
 
         let poe_data = PoeData {
@@ -401,6 +358,7 @@ mod tests {
                 name: "Some Map Boss".to_string(),
                 maps: vec!["Some Map".to_string()],
             }],
+            ..Default::default()
         };
 
         let result: Vec<_> =
@@ -431,6 +389,8 @@ mod tests {
                 sources: vec![map_source.clone()],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
             Record {
                 id: 2,
@@ -442,6 +402,8 @@ mod tests {
                 sources: vec![boss_source.clone()],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
         ];
 
@@ -460,6 +422,7 @@ mod tests {
                 name: "Some Map Boss".to_string(),
                 maps: vec!["Some Map".to_string()],
             }],
+            ..Default::default()
         };
 
         let result = super::cards_by_source(&map_source, &records, &poe_data);
@@ -492,6 +455,8 @@ mod tests {
                 sources: vec![map_source.clone()],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
             Record {
                 id: 2,
@@ -503,6 +468,8 @@ mod tests {
                 sources: vec![boss_source.clone()],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
             Record {
                 id: 3,
@@ -514,6 +481,8 @@ mod tests {
                 sources: vec![other_source],
                 notes: None,
                 verify_sources: vec![],
+                datamined_sources: vec![],
+                hypothesis: None,
             },
         ];
 
@@ -532,6 +501,7 @@ mod tests {
                 name: "Some Map Boss".to_string(),
                 maps: vec!["Some Map".to_string()],
             }],
+            ..Default::default()
         };
 
         let result = super::cards_by_source_types(&["Map".to_string()], &records, &poe_data);
@@ -549,4 +519,56 @@ mod tests {
             .iter()
             .any(|c| c.is_transitive() && c.card() == "Transitive Card"));
     }
+
+    #[test]
+    fn test_sources_by_card() {
+        use crate::poe_data::act::{ActArea, ActAreaId, Bossfight};
+        use serde_json::json;
+
+        let act = ActAreaId::new("1_1_4_1".to_owned());
+        let boss = Source::ActBoss("Brutus, Lord Incarcerator".to_owned());
+        let records = vec![Record {
+            id: 1,
+            greynote: Default::default(),
+            card: "Card".to_string(),
+            tag_hypothesis: None,
+            confidence: Confidence::Ok,
+            remaining_work: Default::default(),
+            sources: vec![boss.clone()],
+            notes: None,
+            verify_sources: vec![],
+            datamined_sources: vec![],
+            hypothesis: None,
+        }];
+        let poe_data = PoeData {
+            acts: vec![ActArea {
+                id: act.clone(),
+                name: "The Upper Prison".to_owned(),
+                act: 1,
+                area_level: 5,
+                image_url: "".to_owned(),
+                has_waypoint: true,
+                has_labyrinth_trial: true,
+                is_town: false,
+                bossfights: vec![Bossfight {
+                    name: "Brutus, Lord Incarcerator".to_owned(),
+                }],
+            }],
+            ..Default::default()
+        };
+
+        let mut sources = super::sources_by_card("Card", &records, &poe_data);
+        sources.sort_by_key(|source| source.is_transitive());
+
+        assert_eq!(sources.len(), 2);
+        assert!(sources[0].is_direct());
+        assert_eq!(sources[0].source(), &boss);
+        assert!(sources[1].is_transitive());
+        assert_eq!(sources[1].source(), &Source::Act(act));
+        assert_eq!(sources[1].status(), VerificationStatus::Done);
+        assert_eq!(
+            serde_json::to_value(&sources[1]).unwrap()["transitive"]["type"],
+            json!("Act Boss")
+        );
+    }
 }
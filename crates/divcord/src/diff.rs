@@ -0,0 +1,290 @@
+//! Semantic diff between two versions of the spreadsheet, for "what changed in divcord" summaries.
+//!
+//! Records are matched by card name. See [`diff`] and [`RecordsDiff::to_markdown`].
+
+use crate::{
+    spreadsheet::record::{Confidence, GreyNote, RemainingWork},
+    Record, Source,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordsDiff {
+    pub added_cards: Vec<String>,
+    pub removed_cards: Vec<String>,
+    /// Changed cards in the order of the new records.
+    pub changed_cards: Vec<CardDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardDiff {
+    pub card: String,
+    /// Record id (spreadsheet row) in the new records.
+    pub id: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sources_added: Vec<Source>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sources_removed: Vec<Source>,
+    /// Sources moved from `verify_sources` to `sources`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sources_verified: Vec<Source>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub verify_sources_added: Vec<Source>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub verify_sources_removed: Vec<Source>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub confidence: Option<Change<Confidence>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub remaining_work: Option<Change<RemainingWork>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub greynote: Option<Change<GreyNote>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub notes: Option<Change<Option<String>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq + Clone> Change<T> {
+    fn of(from: &T, to: &T) -> Option<Self> {
+        (from != to).then(|| Change {
+            from: from.clone(),
+            to: to.clone(),
+        })
+    }
+}
+
+impl CardDiff {
+    pub fn new(old: &Record, new: &Record) -> Self {
+        let (sources_added, sources_removed) = added_removed(&old.sources, &new.sources);
+        let (verify_sources_added, verify_sources_removed) =
+            added_removed(&old.verify_sources, &new.verify_sources);
+        let sources_verified: Vec<Source> = sources_added
+            .iter()
+            .filter(|source| verify_sources_removed.contains(source))
+            .cloned()
+            .collect();
+
+        CardDiff {
+            card: new.card.clone(),
+            id: new.id,
+            sources_added: without(sources_added, &sources_verified),
+            sources_removed,
+            verify_sources_removed: without(verify_sources_removed, &sources_verified),
+            sources_verified,
+            verify_sources_added,
+            confidence: Change::of(&old.confidence, &new.confidence),
+            remaining_work: Change::of(&old.remaining_work, &new.remaining_work),
+            greynote: Change::of(&old.greynote, &new.greynote),
+            notes: Change::of(&old.notes, &new.notes),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources_added.is_empty()
+            && self.sources_removed.is_empty()
+            && self.sources_verified.is_empty()
+            && self.verify_sources_added.is_empty()
+            && self.verify_sources_removed.is_empty()
+            && self.confidence.is_none()
+            && self.remaining_work.is_none()
+            && self.greynote.is_none()
+            && self.notes.is_none()
+    }
+}
+
+/// Compares records of two spreadsheet snapshots.
+pub fn diff(old: &[Record], new: &[Record]) -> RecordsDiff {
+    let old_by_card: HashMap<&str, &Record> = old.iter().map(|r| (r.card.as_str(), r)).collect();
+    let new_by_card: HashMap<&str, &Record> = new.iter().map(|r| (r.card.as_str(), r)).collect();
+
+    let mut diff = RecordsDiff::default();
+    for record in new {
+        match old_by_card.get(record.card.as_str()) {
+            Some(old_record) => {
+                let card_diff = CardDiff::new(old_record, record);
+                if !card_diff.is_empty() {
+                    diff.changed_cards.push(card_diff);
+                }
+            }
+            None => diff.added_cards.push(record.card.clone()),
+        }
+    }
+    diff.removed_cards = old
+        .iter()
+        .filter(|record| !new_by_card.contains_key(record.card.as_str()))
+        .map(|record| record.card.clone())
+        .collect();
+
+    diff
+}
+
+impl RecordsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_cards.is_empty()
+            && self.removed_cards.is_empty()
+            && self.changed_cards.is_empty()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        if self.is_empty() {
+            md.push_str("No changes.\n");
+            return md;
+        }
+
+        if !self.added_cards.is_empty() {
+            writeln!(md, "## New cards\n").unwrap();
+            for card in &self.added_cards {
+                writeln!(md, "- {card}").unwrap();
+            }
+            md.push('\n');
+        }
+        if !self.removed_cards.is_empty() {
+            writeln!(md, "## Removed cards\n").unwrap();
+            for card in &self.removed_cards {
+                writeln!(md, "- {card}").unwrap();
+            }
+            md.push('\n');
+        }
+        if !self.changed_cards.is_empty() {
+            writeln!(md, "## Changed cards\n").unwrap();
+            for card_diff in &self.changed_cards {
+                card_diff.write_markdown(&mut md);
+            }
+        }
+
+        md
+    }
+}
+
+impl CardDiff {
+    fn write_markdown(&self, md: &mut String) {
+        writeln!(md, "### {}\n", self.card).unwrap();
+        write_sources(md, "Verified", &self.sources_verified);
+        write_sources(md, "Sources added", &self.sources_added);
+        write_sources(md, "Sources removed", &self.sources_removed);
+        write_sources(md, "To verify", &self.verify_sources_added);
+        write_sources(md, "No longer to verify", &self.verify_sources_removed);
+        write_change(md, "Confidence", self.confidence.as_ref());
+        write_change(md, "Remaining work", self.remaining_work.as_ref());
+        write_change(md, "Greynote", self.greynote.as_ref());
+        if let Some(Change { from, to }) = &self.notes {
+            match (from, to) {
+                (None, Some(to)) => writeln!(md, "- Notes added: {}", one_line(to)).unwrap(),
+                (Some(_), None) => writeln!(md, "- Notes removed").unwrap(),
+                (_, to) => writeln!(
+                    md,
+                    "- Notes edited: {}",
+                    one_line(to.as_deref().unwrap_or_default())
+                )
+                .unwrap(),
+            }
+        }
+        md.push('\n');
+    }
+}
+
+fn write_sources(md: &mut String, label: &str, sources: &[Source]) {
+    if sources.is_empty() {
+        return;
+    }
+    let sources = sources
+        .iter()
+        .map(|source| format!("{} ({})", source, source._type()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    writeln!(md, "- {label}: {sources}").unwrap();
+}
+
+fn write_change<T: Display>(md: &mut String, label: &str, change: Option<&Change<T>>) {
+    if let Some(Change { from, to }) = change {
+        writeln!(md, "- {label}: {from} → {to}").unwrap();
+    }
+}
+
+fn one_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn added_removed(old: &[Source], new: &[Source]) -> (Vec<Source>, Vec<Source>) {
+    (without(new.to_vec(), old), without(old.to_vec(), new))
+}
+
+fn without(mut sources: Vec<Source>, excluded: &[Source]) -> Vec<Source> {
+    sources.retain(|source| !excluded.contains(source));
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dropsource::predefined::PredefinedSource;
+
+    fn record(card: &str) -> Record {
+        Record {
+            confidence: Confidence::Low,
+            remaining_work: RemainingWork::Confirm,
+            ..Record::test(card)
+        }
+    }
+
+    #[test]
+    fn diffs_records() {
+        let kirac = Source::Predefined(PredefinedSource::KiracMissions);
+        let dunes = Source::Map("Dunes Map".to_owned());
+        let strand = Source::Map("Strand Map".to_owned());
+
+        let old = vec![
+            Record {
+                verify_sources: vec![kirac.clone(), strand.clone()],
+                sources: vec![dunes.clone()],
+                ..record("Rain of Chaos")
+            },
+            record("The Doctor"),
+        ];
+        let new = vec![
+            Record {
+                sources: vec![kirac.clone()],
+                verify_sources: vec![strand.clone()],
+                confidence: Confidence::Done,
+                notes: Some("Confirmed".to_owned()),
+                ..record("Rain of Chaos")
+            },
+            record("The Fiend"),
+        ];
+
+        let diff = diff(&old, &new);
+        assert_eq!(diff.added_cards, ["The Fiend"]);
+        assert_eq!(diff.removed_cards, ["The Doctor"]);
+        assert_eq!(diff.changed_cards.len(), 1);
+
+        let card_diff = &diff.changed_cards[0];
+        assert_eq!(card_diff.sources_verified, [kirac]);
+        assert!(card_diff.sources_added.is_empty());
+        assert!(card_diff.verify_sources_removed.is_empty());
+        assert_eq!(card_diff.sources_removed, [dunes]);
+        assert_eq!(
+            card_diff.confidence,
+            Some(Change {
+                from: Confidence::Low,
+                to: Confidence::Done
+            })
+        );
+        assert_eq!(card_diff.remaining_work, None);
+
+        let md = diff.to_markdown();
+        assert!(md.contains("### Rain of Chaos"));
+        assert!(md.contains("- Confidence: Low → Done"));
+        assert!(md.contains("- Notes added: Confirmed"));
+    }
+}
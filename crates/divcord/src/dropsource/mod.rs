@@ -10,6 +10,7 @@ use strum::IntoEnumIterator;
 
 pub mod id;
 pub mod predefined;
+pub mod suggest;
 
 /// First group of variants of 4 (Act, Map, ActBoss, MapBoss)
 /// is being resolved after s.parse::<PredefinedSource> fails.
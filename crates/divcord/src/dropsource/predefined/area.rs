@@ -34,6 +34,28 @@ impl Area {
         }
     }
 
+    /// Every variant, including the nested ones.
+    pub fn all() -> Vec<Area> {
+        Area::iter()
+            .flat_map(|variant| match variant {
+                Area::AreaSpecific(_) => AreaSpecific::iter().map(Area::AreaSpecific).collect(),
+                Area::AtziriArea(_) => AtziriArea::iter().map(Area::AtziriArea).collect(),
+                Area::UniqueHeistContractOrBoss(_) => UniqueHeistContractOrBoss::iter()
+                    .map(Area::UniqueHeistContractOrBoss)
+                    .collect(),
+                Area::EternalLabTrial(_) => {
+                    EternalLabTrial::iter().map(Area::EternalLabTrial).collect()
+                }
+                Area::TempleOfAtzoatl
+                | Area::AllVaalSideAreas
+                | Area::VaalSideAreas
+                | Area::RedeemerInfluencedMaps
+                | Area::ExpeditionLogbook
+                | Area::LabyrinthTrialAreas => vec![variant],
+            })
+            .collect()
+    }
+
     pub fn _types() -> impl Iterator<Item = String> {
         Area::iter().map(|a| a._type().to_string())
     }
@@ -79,6 +101,21 @@ impl Identified for Area {
             Area::EternalLabTrial(a) => a.id(),
         }
     }
+
+    fn aliases(&self) -> Vec<&str> {
+        match self {
+            Area::AreaSpecific(m) => m.aliases(),
+            Area::AtziriArea(m) => m.aliases(),
+            Area::UniqueHeistContractOrBoss(m) => m.aliases(),
+            Area::EternalLabTrial(m) => m.aliases(),
+            Area::TempleOfAtzoatl
+            | Area::AllVaalSideAreas
+            | Area::VaalSideAreas
+            | Area::RedeemerInfluencedMaps
+            | Area::ExpeditionLogbook
+            | Area::LabyrinthTrialAreas => vec![],
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, EnumIter, PartialEq, Eq, Hash)]
@@ -0,0 +1,135 @@
+//! Predefined sources loaded at runtime, so new league content does not need a release.
+//!
+//! Sources are described in JSON, for example
+//! `[{ "id": "The Dreamer", "type": "Endgame Boss", "aliases": ["Dreamer"] }]`.
+//! Built-in sources take precedence over registered ones with the same id.
+//!
+//! The registry is process-global. Sources ship with the data in
+//! [`crate::poe_data::PoeData::extra_sources`], register them with
+//! [`crate::poe_data::PoeData::register_extra_sources`] before parsing or deserializing records.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExtraSource {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+fn extra() -> &'static RwLock<Vec<ExtraSource>> {
+    static EXTRA: OnceLock<RwLock<Vec<ExtraSource>>> = OnceLock::new();
+    EXTRA.get_or_init(Default::default)
+}
+
+fn read() -> RwLockReadGuard<'static, Vec<ExtraSource>> {
+    extra()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write() -> RwLockWriteGuard<'static, Vec<ExtraSource>> {
+    extra()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Adds sources. A source with already registered id replaces the old one.
+pub fn register(sources: impl IntoIterator<Item = ExtraSource>) {
+    let mut extra = write();
+    for source in sources {
+        extra.retain(|existing| existing.id != source.id);
+        extra.push(source);
+    }
+}
+
+/// Registers sources from JSON array, returns the number of sources.
+pub fn register_json(json: &str) -> serde_json::Result<usize> {
+    let sources: Vec<ExtraSource> = serde_json::from_str(json)?;
+    let len = sources.len();
+    register(sources);
+    Ok(len)
+}
+
+pub fn registered() -> Vec<ExtraSource> {
+    read().clone()
+}
+
+pub fn clear() {
+    write().clear();
+}
+
+/// Registered source by id or alias.
+pub fn find(s: &str) -> Option<ExtraSource> {
+    read()
+        .iter()
+        .find(|source| source.id == s || source.aliases.iter().any(|alias| alias == s))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dropsource::predefined::PredefinedSource, poe_data::PoeData, Source};
+
+    /// Unregisters the sources on drop, so that other tests see the registry as it was.
+    struct Registered(Vec<String>);
+
+    impl Registered {
+        fn new(sources: Vec<ExtraSource>) -> Self {
+            let ids = sources.iter().map(|source| source.id.clone()).collect();
+            register(sources);
+            Registered(ids)
+        }
+    }
+
+    impl Drop for Registered {
+        fn drop(&mut self) {
+            write().retain(|source| !self.0.contains(&source.id));
+        }
+    }
+
+    #[test]
+    fn parses_and_serializes_registered() {
+        let types = Source::types();
+        let _registered = Registered::new(
+            serde_json::from_str(
+                r#"[{ "id": "Test Extra Chest", "type": "Chest", "aliases": ["Extra Chest"] }]"#,
+            )
+            .unwrap(),
+        );
+
+        let predefined = "Extra Chest".parse::<PredefinedSource>().unwrap();
+        let source = Source::Predefined(predefined.clone());
+        let json = serde_json::to_value(&source).unwrap();
+        assert_eq!(json["id"], "Test Extra Chest");
+        assert_eq!(json["type"], "Chest");
+        assert_eq!(json["kind"], "source");
+        assert_eq!(serde_json::from_value::<Source>(json).unwrap(), source);
+
+        assert_eq!(Source::types(), types);
+        assert!(PredefinedSource::all().contains(&predefined));
+    }
+
+    #[test]
+    fn ships_with_poe_data() {
+        let poe_data = PoeData {
+            extra_sources: vec![ExtraSource {
+                id: "Test Poe Data Chest".to_owned(),
+                _type: "Chest".to_owned(),
+                aliases: vec![],
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&poe_data).unwrap();
+        let poe_data: PoeData = serde_json::from_str(&json).unwrap();
+        let _registered = Registered(vec!["Test Poe Data Chest".to_owned()]);
+        assert!(find("Test Poe Data Chest").is_none());
+
+        poe_data.register_extra_sources();
+        assert!("Test Poe Data Chest".parse::<PredefinedSource>().is_ok());
+    }
+}
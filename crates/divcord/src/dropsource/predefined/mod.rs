@@ -2,12 +2,14 @@
 
 use super::id::Identified;
 use area::Area;
+use extra::ExtraSource;
 use monster::UniqueMonster;
 use other::{Chest, Strongbox, Vendor};
 use std::str::FromStr;
 use strum::IntoEnumIterator;
 
 pub mod area;
+pub mod extra;
 pub mod monster;
 mod other;
 
@@ -28,6 +30,9 @@ pub enum PredefinedSource {
     BreachEncountersKeepers,
     #[default]
     Disabled,
+
+    /// Registered at runtime, see [`extra`].
+    Extra(ExtraSource),
 }
 
 impl PredefinedSource {
@@ -37,11 +42,51 @@ impl PredefinedSource {
         PredefinedSource::iter().for_each(|variant| match variant {
             PredefinedSource::UniqueMonster(_) => vec.extend(UniqueMonster::_types()),
             PredefinedSource::Area(_) => vec.extend(Area::_types()),
+            PredefinedSource::Extra(_) => {
+                for source in extra::registered() {
+                    if !vec.contains(&source._type) {
+                        vec.push(source._type);
+                    }
+                }
+            }
             _ => vec.push(variant._type().to_string()),
         });
 
         vec
     }
+
+    /// Every predefined source, including the nested ones.
+    pub fn all() -> Vec<PredefinedSource> {
+        PredefinedSource::iter()
+            .flat_map(|variant| match variant {
+                PredefinedSource::UniqueMonster(_) => UniqueMonster::all()
+                    .into_iter()
+                    .map(PredefinedSource::UniqueMonster)
+                    .collect(),
+                PredefinedSource::Area(_) => Area::all()
+                    .into_iter()
+                    .map(PredefinedSource::Area)
+                    .collect(),
+                PredefinedSource::Chest(_) => Chest::iter().map(PredefinedSource::Chest).collect(),
+                PredefinedSource::Strongbox(_) => {
+                    Strongbox::iter().map(PredefinedSource::Strongbox).collect()
+                }
+                PredefinedSource::Vendor(_) => {
+                    Vendor::iter().map(PredefinedSource::Vendor).collect()
+                }
+                PredefinedSource::Extra(_) => extra::registered()
+                    .into_iter()
+                    .map(PredefinedSource::Extra)
+                    .collect(),
+                PredefinedSource::KiracMissions
+                | PredefinedSource::MaelstromOfChaosWithBarrelSextant
+                | PredefinedSource::Delirium
+                | PredefinedSource::DeliriumCurrencyRewards
+                | PredefinedSource::BreachEncountersKeepers
+                | PredefinedSource::Disabled => vec![variant],
+            })
+            .collect()
+    }
 }
 
 /// `s.parse::<Source>` validates only predefined sources.
@@ -70,6 +115,7 @@ impl FromStr for PredefinedSource {
                 .or_else(|_| Vendor::from_str(s).map(Self::Vendor))
                 .or_else(|_| Strongbox::from_str(s).map(Self::Strongbox))
                 .or_else(|_| Chest::from_str(s).map(Self::Chest))
+                .or_else(|_| extra::find(s).map(Self::Extra).ok_or(()))
                 .map_err(|_| UnknownPredefinedSource(s.to_owned())),
         }
     }
@@ -91,6 +137,7 @@ impl PredefinedSource {
             }
             PredefinedSource::KiracMissions => "Kirac Missions",
             PredefinedSource::BreachEncountersKeepers => "Breach encounters (Keepers)",
+            PredefinedSource::Extra(source) => &source._type,
         }
     }
 }
@@ -111,6 +158,24 @@ impl Identified for PredefinedSource {
             }
             PredefinedSource::KiracMissions => "Kirac Missions",
             PredefinedSource::BreachEncountersKeepers => "Breach encounters (Keepers)",
+            PredefinedSource::Extra(source) => &source.id,
+        }
+    }
+
+    fn aliases(&self) -> Vec<&str> {
+        match self {
+            PredefinedSource::UniqueMonster(m) => m.aliases(),
+            PredefinedSource::Area(a) => a.aliases(),
+            PredefinedSource::Chest(chest) => chest.aliases(),
+            PredefinedSource::Strongbox(strongbox) => strongbox.aliases(),
+            PredefinedSource::Vendor(vendor) => vendor.aliases(),
+            PredefinedSource::Extra(source) => source.aliases.iter().map(String::as_str).collect(),
+            PredefinedSource::KiracMissions
+            | PredefinedSource::MaelstromOfChaosWithBarrelSextant
+            | PredefinedSource::Delirium
+            | PredefinedSource::DeliriumCurrencyRewards
+            | PredefinedSource::BreachEncountersKeepers
+            | PredefinedSource::Disabled => vec![],
         }
     }
 }
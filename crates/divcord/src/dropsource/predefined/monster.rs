@@ -175,6 +175,54 @@ impl Identified for UniqueMonster {
             UniqueMonster::EldritchPerfectionMonster(m) => m.id(),
         }
     }
+
+    fn aliases(&self) -> Vec<&str> {
+        match self {
+            UniqueMonster::Incarnation(m) => m.aliases(),
+            UniqueMonster::BreachlordBossDomain(m) => m.aliases(),
+            UniqueMonster::Architect(m) => m.aliases(),
+            UniqueMonster::ShaperGuardianBoss(m) => m.aliases(),
+            UniqueMonster::SyndicateMember(m) => m.aliases(),
+            UniqueMonster::Elderslayer(m) => m.aliases(),
+            UniqueMonster::ElderGuardianBoss(m) => m.aliases(),
+            UniqueMonster::RogueExile(m) => m.aliases(),
+            UniqueMonster::FemaleRogueExile(m) => m.aliases(),
+            UniqueMonster::AbyssLichBoss(m) => m.aliases(),
+            UniqueMonster::MapsOnly(m) => m.aliases(),
+            UniqueMonster::HarbingerPortal(m) => m.aliases(),
+            UniqueMonster::EndgameBoss(m) => m.aliases(),
+            UniqueMonster::DelveBoss(m) => m.aliases(),
+            UniqueMonster::BeastBoss(m) => m.aliases(),
+            UniqueMonster::HeistBoss(m) => m.aliases(),
+            UniqueMonster::BeyondBoss(m) => m.aliases(),
+            UniqueMonster::ExpeditionLogbookBoss(m) => m.aliases(),
+            UniqueMonster::BetrayalCatarina(m) => m.aliases(),
+            UniqueMonster::OshabiBoss(m) => m.aliases(),
+            UniqueMonster::EldritchPerfectionMonster(m) => m.aliases(),
+            UniqueMonster::ShaperMiniBoss(m) => m.aliases(),
+            UniqueMonster::KingInTheMists
+            | UniqueMonster::Vruun
+            | UniqueMonster::HivebornMonsters
+            | UniqueMonster::StygianSpire
+            | UniqueMonster::BreachMonsters
+            | UniqueMonster::Allt3Andt4HarvestMonsters
+            | UniqueMonster::MavensInvitationTheFeared
+            | UniqueMonster::UulNetolInBreachstones
+            | UniqueMonster::VaalOmnitect
+            | UniqueMonster::Metamorph
+            | UniqueMonster::NullPortal
+            | UniqueMonster::VaalFleshMerchant
+            | UniqueMonster::AllIncursionArchitectsInAlvaMission
+            | UniqueMonster::AllIncursionArchitectsInTemple
+            | UniqueMonster::AllAbyssMonsters
+            | UniqueMonster::AllScourgeBeyondDemons
+            | UniqueMonster::AllRogueExiles
+            | UniqueMonster::CortexVenarius
+            | UniqueMonster::Argus
+            | UniqueMonster::AllInvasionBosses
+            | UniqueMonster::AllVaalSideAreaBosses => vec![],
+        }
+    }
 }
 
 impl std::fmt::Display for UniqueMonster {
@@ -184,6 +232,101 @@ impl std::fmt::Display for UniqueMonster {
 }
 
 impl UniqueMonster {
+    /// Every variant, including the nested ones.
+    pub fn all() -> Vec<UniqueMonster> {
+        UniqueMonster::iter()
+            .flat_map(|variant| match variant {
+                UniqueMonster::Incarnation(_) => Incarnation::iter()
+                    .map(UniqueMonster::Incarnation)
+                    .collect(),
+                UniqueMonster::BreachlordBossDomain(_) => BreachlordBossDomain::iter()
+                    .map(UniqueMonster::BreachlordBossDomain)
+                    .collect(),
+                UniqueMonster::Architect(_) => {
+                    Architect::iter().map(UniqueMonster::Architect).collect()
+                }
+                UniqueMonster::ShaperGuardianBoss(_) => ShaperGuardianBoss::iter()
+                    .map(UniqueMonster::ShaperGuardianBoss)
+                    .collect(),
+                UniqueMonster::SyndicateMember(_) => SyndicateMember::iter()
+                    .map(UniqueMonster::SyndicateMember)
+                    .collect(),
+                UniqueMonster::Elderslayer(_) => Elderslayer::iter()
+                    .map(UniqueMonster::Elderslayer)
+                    .collect(),
+                UniqueMonster::ElderGuardianBoss(_) => ElderGuardianBoss::iter()
+                    .map(UniqueMonster::ElderGuardianBoss)
+                    .collect(),
+                UniqueMonster::RogueExile(_) => {
+                    RogueExile::iter().map(UniqueMonster::RogueExile).collect()
+                }
+                UniqueMonster::FemaleRogueExile(_) => FemaleRogueExile::iter()
+                    .map(UniqueMonster::FemaleRogueExile)
+                    .collect(),
+                UniqueMonster::AbyssLichBoss(_) => AbyssLichBoss::iter()
+                    .map(UniqueMonster::AbyssLichBoss)
+                    .collect(),
+                UniqueMonster::MapsOnly(_) => {
+                    MapsOnly::iter().map(UniqueMonster::MapsOnly).collect()
+                }
+                UniqueMonster::HarbingerPortal(_) => HarbingerPortal::iter()
+                    .map(UniqueMonster::HarbingerPortal)
+                    .collect(),
+                UniqueMonster::EndgameBoss(_) => EndgameBoss::iter()
+                    .map(UniqueMonster::EndgameBoss)
+                    .collect(),
+                UniqueMonster::DelveBoss(_) => {
+                    DelveBoss::iter().map(UniqueMonster::DelveBoss).collect()
+                }
+                UniqueMonster::BeastBoss(_) => {
+                    BeastBoss::iter().map(UniqueMonster::BeastBoss).collect()
+                }
+                UniqueMonster::HeistBoss(_) => {
+                    HeistBoss::iter().map(UniqueMonster::HeistBoss).collect()
+                }
+                UniqueMonster::BeyondBoss(_) => {
+                    BeyondBoss::iter().map(UniqueMonster::BeyondBoss).collect()
+                }
+                UniqueMonster::ExpeditionLogbookBoss(_) => ExpeditionLogbookBoss::iter()
+                    .map(UniqueMonster::ExpeditionLogbookBoss)
+                    .collect(),
+                UniqueMonster::BetrayalCatarina(_) => BetrayalCatarina::iter()
+                    .map(UniqueMonster::BetrayalCatarina)
+                    .collect(),
+                UniqueMonster::OshabiBoss(_) => {
+                    OshabiBoss::iter().map(UniqueMonster::OshabiBoss).collect()
+                }
+                UniqueMonster::EldritchPerfectionMonster(_) => EldritchPerfectionMonster::iter()
+                    .map(UniqueMonster::EldritchPerfectionMonster)
+                    .collect(),
+                UniqueMonster::ShaperMiniBoss(_) => ShaperMiniBoss::iter()
+                    .map(UniqueMonster::ShaperMiniBoss)
+                    .collect(),
+                UniqueMonster::KingInTheMists
+                | UniqueMonster::Vruun
+                | UniqueMonster::HivebornMonsters
+                | UniqueMonster::StygianSpire
+                | UniqueMonster::BreachMonsters
+                | UniqueMonster::Allt3Andt4HarvestMonsters
+                | UniqueMonster::MavensInvitationTheFeared
+                | UniqueMonster::UulNetolInBreachstones
+                | UniqueMonster::VaalOmnitect
+                | UniqueMonster::Metamorph
+                | UniqueMonster::NullPortal
+                | UniqueMonster::VaalFleshMerchant
+                | UniqueMonster::AllIncursionArchitectsInAlvaMission
+                | UniqueMonster::AllIncursionArchitectsInTemple
+                | UniqueMonster::AllAbyssMonsters
+                | UniqueMonster::AllScourgeBeyondDemons
+                | UniqueMonster::AllRogueExiles
+                | UniqueMonster::CortexVenarius
+                | UniqueMonster::Argus
+                | UniqueMonster::AllInvasionBosses
+                | UniqueMonster::AllVaalSideAreaBosses => vec![variant],
+            })
+            .collect()
+    }
+
     pub fn _types() -> impl Iterator<Item = String> {
         UniqueMonster::iter().map(|a| a._type().to_string())
     }
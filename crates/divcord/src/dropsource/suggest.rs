@@ -0,0 +1,133 @@
+//! "Did you mean" suggestions for unknown drop sources.
+
+use super::{id::Identified, predefined::PredefinedSource};
+use crate::poe_data::PoeData;
+use std::{collections::HashSet, sync::OnceLock};
+
+/// How many suggestions to attach to an unknown source.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Every name the parser knows: predefined ids and aliases, maps, map bosses, act areas and act bosses.
+pub fn known_names(poe_data: &PoeData) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let predefined = PredefinedSource::all().into_iter().flat_map(|source| {
+        let mut names = vec![source.id().to_owned()];
+        names.extend(source.aliases().into_iter().map(str::to_owned));
+        names
+    });
+    let maps = poe_data.maps.iter().map(|map| map.name.clone());
+    let mapbosses = poe_data.mapbosses.iter().map(|boss| boss.name.clone());
+    let acts = poe_data.acts.iter().flat_map(|act| {
+        std::iter::once(act.name.clone()).chain(act.bossfights.iter().map(|b| b.name.clone()))
+    });
+
+    predefined
+        .chain(maps)
+        .chain(mapbosses)
+        .chain(acts)
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Closest known names to the unknown one, best first.
+pub fn suggest(name: &str, poe_data: &PoeData) -> Vec<String> {
+    closest(name, poe_data.known_names.get(poe_data), MAX_SUGGESTIONS)
+}
+
+/// [`known_names`] of the [`PoeData`] it is stored in, collected on the first unknown source.
+/// Clones start empty, so that the names are collected again for the changed data.
+#[derive(Default)]
+pub struct KnownNames(OnceLock<Vec<String>>);
+
+impl KnownNames {
+    pub fn get(&self, poe_data: &PoeData) -> &[String] {
+        self.0.get_or_init(|| known_names(poe_data))
+    }
+}
+
+impl Clone for KnownNames {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for KnownNames {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("KnownNames")
+            .field(&self.0.get().map(Vec::len))
+            .finish()
+    }
+}
+
+pub fn closest(name: &str, known: &[String], max: usize) -> Vec<String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return vec![];
+    }
+
+    let mut scored = known
+        .iter()
+        .filter_map(|known_name| {
+            let lowercased = known_name.to_lowercase();
+            let distance = levenshtein(&name, &lowercased)
+                // "Dunes" for "Dunes Map"
+                .min(levenshtein(&name, lowercased.trim_end_matches(" map")));
+            let threshold = (name.chars().count().max(lowercased.chars().count()) / 3).max(1);
+            (distance <= threshold).then_some((distance, known_name))
+        })
+        .collect::<Vec<_>>();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(max)
+        .map(|(_, known_name)| known_name.to_owned())
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a_char == *b_char {
+                diagonal
+            } else {
+                diagonal.min(above).min(row[j]) + 1
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_closest() {
+        let known = ["Venarius", "Cortex", "Vortex Map", "The Doctor"].map(str::to_owned);
+        assert_eq!(closest("Cortx", &known, 3), ["Cortex", "Vortex Map"]);
+        assert_eq!(closest("vortex", &known, 3), ["Vortex Map", "Cortex"]);
+        assert!(closest("Kirac Missions", &known, 3).is_empty());
+    }
+
+    #[test]
+    fn knows_predefined_aliases() {
+        let names = known_names(&PoeData::default());
+        assert!(names.contains(&"Kirac Missions".to_owned()));
+        assert!(names.contains(&"Darnaw's Landing".to_owned()));
+    }
+
+    #[test]
+    fn collects_known_names_once() {
+        let poe_data = PoeData::default();
+        assert_eq!(suggest("Kirac Mission", &poe_data), ["Kirac Missions"]);
+        let names = poe_data.known_names.get(&poe_data).as_ptr();
+        suggest("Delirim", &poe_data);
+        assert_eq!(poe_data.known_names.get(&poe_data).as_ptr(), names);
+    }
+}
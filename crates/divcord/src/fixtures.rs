@@ -0,0 +1,107 @@
+//! Minimal values of the data types for unit tests.
+
+use crate::{
+    poe_data::{
+        act::{ActArea, ActAreaId},
+        cards::{Card, CardsData},
+        maps::Map,
+    },
+    spreadsheet::record::{Confidence, Dumb, GreyNote, Record, RemainingWork},
+};
+
+impl Card {
+    pub fn test(name: &str, min_level: u32) -> Self {
+        Card {
+            slug: String::new(),
+            name: name.to_owned(),
+            min_level,
+            id: String::new(),
+            flavour_text: String::new(),
+            art_filename: String::new(),
+            stack_size: 1,
+            weights: Default::default(),
+            price: None,
+            league: None,
+            disabled: false,
+            atlas_maps: vec![],
+        }
+    }
+}
+
+impl CardsData {
+    pub fn test(cards: impl IntoIterator<Item = Card>) -> Self {
+        CardsData {
+            dict: cards
+                .into_iter()
+                .map(|card| (card.name.clone(), card))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Map {
+    pub fn test(name: &str) -> Self {
+        Map {
+            name: name.to_owned(),
+            tier: 1,
+            unique: false,
+            icon: String::new(),
+            slug: String::new(),
+            atlas_cards: vec![],
+        }
+    }
+}
+
+impl ActArea {
+    pub fn test(id: &str, name: &str, act: u8, area_level: u8) -> Self {
+        ActArea {
+            id: ActAreaId::new(id.to_owned()),
+            name: name.to_owned(),
+            act,
+            area_level,
+            image_url: String::new(),
+            has_waypoint: true,
+            has_labyrinth_trial: false,
+            is_town: false,
+            bossfights: vec![],
+        }
+    }
+}
+
+impl Record {
+    /// Done record of the third sheet row, without sources.
+    pub fn test(card: &str) -> Self {
+        Record {
+            id: 3,
+            greynote: GreyNote::Empty,
+            card: card.to_owned(),
+            tag_hypothesis: None,
+            confidence: Confidence::Done,
+            remaining_work: RemainingWork::NotApplicable,
+            sources: vec![],
+            notes: None,
+            verify_sources: vec![],
+            datamined_sources: vec![],
+            hypothesis: None,
+        }
+    }
+}
+
+impl Dumb {
+    /// Done row of the third sheet row, without drops.
+    pub fn test(card: &str) -> Self {
+        Dumb {
+            id: 3,
+            greynote: GreyNote::Empty,
+            card: card.to_owned(),
+            tag_hypothesis: None,
+            confidence: Confidence::Done,
+            remaining_work: RemainingWork::NotApplicable,
+            drops: vec![],
+            drops_datamined: vec![],
+            drops_to_verify: vec![],
+            notes: None,
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Lookups of cards by source and sources by card, built once from records and [`PoeData`].
+
+use crate::{
+    cards::{
+        CardBySource, Direct, DirectSource, SourceAndCards, SourceByCard, Transitive,
+        TransitiveSource, VerificationStatus,
+    },
+    poe_data::{act::ActAreaId, PoeData},
+    spreadsheet::record::GreyNote,
+    Record, Source,
+};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Default)]
+pub struct DivcordIndex {
+    /// Cards of records and atlas cards of maps.
+    direct: HashMap<Source, HashSet<Direct>>,
+    /// Direct, transitive and atlas sources of every card.
+    sources_by_card: HashMap<String, HashSet<SourceByCard>>,
+    /// Bosses by lowercased map name.
+    map_bosses: HashMap<String, Vec<Source>>,
+    act_bosses: HashMap<ActAreaId, Vec<Source>>,
+    /// Maps of [`PoeData`].
+    maps: Vec<String>,
+    /// Non-town act areas of [`PoeData`].
+    acts: Vec<ActAreaId>,
+    /// Levels of maps and act areas.
+    area_levels: HashMap<Source, u32>,
+    /// Global drop cards with their drop level, lowest first.
+    global_drops: Vec<(String, u32)>,
+}
+
+impl DivcordIndex {
+    pub fn new(records: &[Record], poe_data: &PoeData) -> Self {
+        let mut index = DivcordIndex::default();
+
+        for record in records {
+            for (sources, status) in [
+                (&record.sources, VerificationStatus::Done),
+                (&record.verify_sources, VerificationStatus::Verify),
+                (&record.datamined_sources, VerificationStatus::Datamined),
+            ] {
+                for source in sources {
+                    index.insert_direct(source.clone(), record.card.clone(), status);
+                }
+            }
+        }
+
+        for record in records {
+            if record.greynote != GreyNote::GlobalDrop {
+                continue;
+            }
+            // Without card data the drop level is unknown
+            if let Some(card) = poe_data.cards.dict.get(&record.card) {
+                if !card.disabled {
+                    index
+                        .global_drops
+                        .push((record.card.clone(), card.min_level));
+                }
+            }
+        }
+        index.global_drops.sort_by_key(|(_, min_level)| *min_level);
+
+        for map in &poe_data.maps {
+            index.maps.push(map.name.clone());
+            let source = Source::Map(map.name.clone());
+            index.area_levels.insert(source.clone(), map.level());
+            index.direct.entry(source.clone()).or_default();
+            for card in &map.atlas_cards {
+                index.insert_direct(source.clone(), card.clone(), VerificationStatus::Atlas);
+            }
+        }
+
+        for boss in &poe_data.mapbosses {
+            for map in &boss.maps {
+                index
+                    .map_bosses
+                    .entry(map.to_lowercase())
+                    .or_default()
+                    .push(Source::from(boss.clone()));
+            }
+        }
+
+        for act in &poe_data.acts {
+            if !act.is_town {
+                index.acts.push(act.id.clone());
+            }
+            index
+                .area_levels
+                .insert(Source::Act(act.id.clone()), u32::from(act.area_level));
+            index.act_bosses.insert(
+                act.id.clone(),
+                act.bossfights.iter().cloned().map(Source::from).collect(),
+            );
+        }
+
+        index.index_sources_by_card();
+
+        index
+    }
+
+    fn insert_direct(&mut self, source: Source, card: String, status: VerificationStatus) {
+        self.direct
+            .entry(source)
+            .or_default()
+            .insert(Direct { card, status });
+    }
+
+    /// Reverse of direct and transitive lookups, for [`DivcordIndex::sources_by_card`].
+    fn index_sources_by_card(&mut self) {
+        let mut sources_by_card: HashMap<String, HashSet<SourceByCard>> = HashMap::new();
+        for (source, cards) in &self.direct {
+            for direct in cards {
+                sources_by_card
+                    .entry(direct.card.clone())
+                    .or_default()
+                    .insert(SourceByCard::Direct(DirectSource {
+                        source: source.clone(),
+                        status: direct.status,
+                    }));
+            }
+        }
+
+        let maps = self.maps.iter().map(|map| Source::Map(map.clone()));
+        let acts = self.act_bosses.keys().cloned().map(Source::Act);
+        for source in maps.chain(acts) {
+            for transitive in self.transitive_cards(&source) {
+                sources_by_card.entry(transitive.card).or_default().insert(
+                    SourceByCard::Transitive(TransitiveSource {
+                        source: source.clone(),
+                        status: transitive.status,
+                        transitive: transitive.transitive,
+                    }),
+                );
+            }
+        }
+
+        self.sources_by_card = sources_by_card;
+    }
+
+    pub fn direct_cards(&self, source: &Source) -> impl Iterator<Item = &Direct> {
+        self.direct.get(source).into_iter().flatten()
+    }
+
+    /// Bosses of the map or act area.
+    pub fn transitive_sources(&self, source: &Source) -> &[Source] {
+        let bosses = match source {
+            Source::Map(map) => self.map_bosses.get(&map.to_lowercase()),
+            Source::Act(act) => self.act_bosses.get(act),
+            _ => None,
+        };
+        bosses.map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn transitive_cards(&self, source: &Source) -> Vec<Transitive> {
+        self.transitive_sources(source)
+            .iter()
+            .flat_map(|transitive| {
+                self.direct_cards(transitive).map(|direct| Transitive {
+                    card: direct.card.clone(),
+                    status: direct.status,
+                    transitive: transitive.clone(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn cards_by_source(&self, source: &Source) -> Vec<CardBySource> {
+        let transitive = self
+            .transitive_cards(source)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        self.direct_cards(source)
+            .cloned()
+            .map(CardBySource::Direct)
+            .chain(transitive.into_iter().map(CardBySource::Transitive))
+            .collect()
+    }
+
+    pub fn cards_by_source_types(&self, source_types: &[String]) -> Vec<SourceAndCards> {
+        let is_wanted = |source: &Source| source_types.iter().any(|s| source._type() == *s);
+        let mut hash_map: HashMap<Source, HashSet<CardBySource>> = HashMap::new();
+
+        // 1. Direct drops, atlas cards of maps
+        for (source, cards) in &self.direct {
+            if is_wanted(source) {
+                hash_map
+                    .entry(source.clone())
+                    .or_default()
+                    .extend(cards.iter().cloned().map(CardBySource::Direct));
+            }
+        }
+
+        // 2. Transitive sources of maps (map bosses) and acts (boss-specific cards)
+        let maps = self.maps.iter().map(|map| Source::Map(map.clone()));
+        let acts = self.acts.iter().map(|act| Source::Act(act.clone()));
+        for source in maps.chain(acts) {
+            if !is_wanted(&source) {
+                continue;
+            }
+            let transitive = self.transitive_cards(&source);
+            if !transitive.is_empty() {
+                hash_map
+                    .entry(source)
+                    .or_default()
+                    .extend(transitive.into_iter().map(CardBySource::Transitive));
+            }
+        }
+
+        hash_map
+            .into_iter()
+            .map(|(source, cards)| SourceAndCards {
+                source,
+                cards: Vec::from_iter(cards),
+            })
+            .collect()
+    }
+
+    /// Level of the map or act area.
+    pub fn area_level(&self, source: &Source) -> Option<u32> {
+        self.area_levels.get(source).copied()
+    }
+
+    /// Global drop cards, that can drop at the area level.
+    pub fn global_drop_cards(&self, area_level: u32) -> impl Iterator<Item = &str> {
+        self.global_drops
+            .iter()
+            .take_while(move |(_, min_level)| *min_level <= area_level)
+            .map(|(card, _)| card.as_str())
+    }
+
+    /// Like [`DivcordIndex::cards_by_source`], plus global drop cards
+    /// eligible at the level of the map or act area.
+    pub fn cards_by_source_with_global_drops(&self, source: &Source) -> Vec<CardBySource> {
+        let mut cards = self.cards_by_source(source);
+        let Some(area_level) = self.area_level(source) else {
+            return cards;
+        };
+
+        let listed = cards
+            .iter()
+            .map(|card| card.card().to_owned())
+            .collect::<HashSet<_>>();
+        let global = self
+            .global_drop_cards(area_level)
+            .filter(|card| !listed.contains(*card))
+            .map(|card| {
+                CardBySource::Direct(Direct {
+                    card: card.to_owned(),
+                    status: VerificationStatus::Global,
+                })
+            })
+            .collect::<Vec<_>>();
+        cards.extend(global);
+        cards
+    }
+
+    /// Direct, transitive and atlas sources of the card.
+    pub fn sources_by_card(&self, card: &str) -> impl Iterator<Item = &SourceByCard> {
+        self.sources_by_card.get(card).into_iter().flatten()
+    }
+
+    pub fn map_bosses(&self, map: &str) -> &[Source] {
+        self.map_bosses
+            .get(&map.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn act_bosses(&self, act: &ActAreaId) -> &[Source] {
+        self.act_bosses
+            .get(act)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dropsource::predefined::PredefinedSource,
+        poe_data::{mapbosses::MapBoss, maps::Map},
+    };
+
+    fn record(card: &str, sources: Vec<Source>, verify_sources: Vec<Source>) -> Record {
+        Record {
+            sources,
+            verify_sources,
+            ..Record::test(card)
+        }
+    }
+
+    #[test]
+    fn indexes_sources_by_card() {
+        let map = Source::Map("Some Map".to_owned());
+        let boss = Source::MapBoss("Some Map Boss".to_owned());
+        let delirium = Source::Predefined(PredefinedSource::Delirium);
+        let records = vec![
+            record("Boss Card", vec![boss.clone()], vec![delirium.clone()]),
+            record("Other Card", vec![delirium.clone()], vec![]),
+        ];
+        let poe_data = PoeData {
+            maps: vec![Map {
+                atlas_cards: vec!["Atlas Card".to_owned()],
+                ..Map::test("Some Map")
+            }],
+            mapbosses: vec![MapBoss {
+                name: "Some Map Boss".to_owned(),
+                maps: vec!["Some Map".to_owned()],
+            }],
+            ..Default::default()
+        };
+
+        let index = DivcordIndex::new(&records, &poe_data);
+        assert_eq!(index.map_bosses("some map").len(), 1);
+
+        let sources = index.sources_by_card("Boss Card").collect::<HashSet<_>>();
+        assert_eq!(sources.len(), 3);
+        assert!(
+            sources.contains(&SourceByCard::Transitive(TransitiveSource {
+                source: map.clone(),
+                status: VerificationStatus::Done,
+                transitive: boss,
+            }))
+        );
+        assert!(sources.contains(&SourceByCard::Direct(DirectSource {
+            source: delirium.clone(),
+            status: VerificationStatus::Verify,
+        })));
+        assert!(index.sources_by_card("Atlas Card").any(|source| *source
+            == SourceByCard::Direct(DirectSource {
+                source: map.clone(),
+                status: VerificationStatus::Atlas,
+            })));
+
+        let by_types = index.cards_by_source_types(&["Map".to_owned()]);
+        assert_eq!(by_types.len(), 1);
+        assert_eq!(by_types[0].cards.len(), 2);
+        assert_eq!(index.cards_by_source(&delirium).len(), 2);
+    }
+
+    #[test]
+    fn lists_global_drops_by_area_level() {
+        use crate::poe_data::cards::{Card, CardsData};
+
+        let global = |card: &str| Record {
+            greynote: GreyNote::GlobalDrop,
+            ..record(card, vec![], vec![])
+        };
+        let records = vec![
+            global("Rain of Chaos"),
+            global("The Doctor"),
+            global("Not In Poe Data"),
+        ];
+        let poe_data = PoeData {
+            cards: CardsData::test([Card::test("Rain of Chaos", 0), Card::test("The Doctor", 70)]),
+            maps: vec![Map::test("Dunes Map")],
+            ..Default::default()
+        };
+
+        let index = DivcordIndex::new(&records, &poe_data);
+        assert_eq!(
+            index.global_drop_cards(68).collect::<Vec<_>>(),
+            ["Rain of Chaos"]
+        );
+        assert_eq!(index.global_drop_cards(70).count(), 2);
+        assert_eq!(
+            index.global_drop_cards(0).collect::<Vec<_>>(),
+            ["Rain of Chaos"]
+        );
+
+        let cards = index.cards_by_source_with_global_drops(&Source::Map("Dunes Map".to_owned()));
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].status(), VerificationStatus::Global);
+    }
+}
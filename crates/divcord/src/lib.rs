@@ -2,14 +2,22 @@
 
 pub mod cards;
 pub mod consts;
+pub mod diff;
 pub mod dropsource;
+pub mod index;
+pub mod lint;
 pub mod parse;
 pub mod poe_data;
 pub mod spreadsheet;
 
+#[cfg(test)]
+mod fixtures;
+
 pub use crate::{
-    cards::{cards_by_source, cards_by_source_types, CardBySource},
+    cards::{cards_by_source, cards_by_source_types, sources_by_card, CardBySource, SourceByCard},
     dropsource::Source,
+    index::DivcordIndex,
+    lint::{LintConfig, LintReport},
     parse::{records, records_iter, records_with_collect_all_errors, ParseRecordError},
     spreadsheet::{record::Record, Spreadsheet},
 };
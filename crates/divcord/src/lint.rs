@@ -0,0 +1,428 @@
+//! Sortable report of spreadsheet errors for divcord editors.
+//!
+//! [`ParseRecordError`]s are turned into [`Lint`]s with a code, severity, cell and suggested fix.
+//! Severity of every check can be configured with [`LintConfig`].
+
+use crate::{
+    parse::{unknown_source_message, ParseRecordError, ParseSourceError, ParseSourceErrorKind},
+    spreadsheet::{
+        record::{ParseCardNameError, ParseDumbErrKind, ParseDumbError},
+        SpreadsheetLayout,
+    },
+};
+use googlesheets::Column;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt::Write};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+)]
+pub enum LintCode {
+    UnknownDropSource,
+    ActsMustBeItalic,
+    SourceOrVerifyIsExpectedButEmpty,
+    GreynoteDisabledButCardNotLegacy,
+    LegacyCardShouldBeMarkedAsDisabled,
+    ConfidenceNoneButHasSources,
+    SourceIsDoneAndVerifyAtSameTime,
+    AreaLevelIsLowerThanCardMinLevel,
+    InvalidGreynote,
+    InvalidCardName,
+    InvalidConfidence,
+    InvalidRemainingWork,
+    InvalidStyledCell,
+    DuplicateDropSource,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    #[strum(to_string = "error")]
+    Error,
+    #[strum(to_string = "warning")]
+    Warning,
+}
+
+/// Checks, that are reported as warnings. All other checks are errors.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LintConfig {
+    pub warnings: HashSet<LintCode>,
+}
+
+impl LintConfig {
+    pub fn warn(mut self, code: LintCode) -> Self {
+        self.warnings.insert(code);
+        self
+    }
+
+    pub fn severity(&self, code: LintCode) -> Severity {
+        match self.warnings.contains(&code) {
+            true => Severity::Warning,
+            false => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Lint {
+    pub code: LintCode,
+    pub severity: Severity,
+    pub card: String,
+    /// Spreadsheet row, same as record id.
+    pub row: usize,
+    pub column: Column,
+    /// Offending cell text, if it is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    pub record_url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub lints: Vec<Lint>,
+}
+
+impl LintReport {
+    pub fn new(
+        errors: &[ParseRecordError],
+        layout: &SpreadsheetLayout,
+        config: &LintConfig,
+    ) -> Self {
+        let mut report = LintReport::default();
+        for error in errors {
+            report.push(error, layout, config);
+        }
+        report.sort();
+        report
+    }
+
+    pub fn push(
+        &mut self,
+        error: &ParseRecordError,
+        layout: &SpreadsheetLayout,
+        config: &LintConfig,
+    ) {
+        match error {
+            ParseRecordError::ParseDumb(error) => self
+                .lints
+                .push(Lint::from_dumb_error(error, layout, config)),
+            ParseRecordError::ParseDropSources(errors) => self.lints.extend(
+                errors
+                    .iter()
+                    .map(|error| Lint::from_source_error(error, layout, config)),
+            ),
+        }
+    }
+
+    /// Errors first, then by row and column.
+    pub fn sort(&mut self) {
+        self.lints.sort_by(|a, b| {
+            (a.severity, a.row, a.column, a.code).cmp(&(b.severity, b.row, b.column, b.code))
+        });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Lint> {
+        self.lints
+            .iter()
+            .filter(|lint| lint.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Lint> {
+        self.lints
+            .iter()
+            .filter(|lint| lint.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        writeln!(
+            md,
+            "{} errors, {} warnings\n",
+            self.errors().count(),
+            self.warnings().count()
+        )
+        .unwrap();
+        if self.lints.is_empty() {
+            return md;
+        }
+
+        md.push_str("| Severity | Code | Cell | Card | Text | Message | Suggestion |\n");
+        md.push_str("|---|---|---|---|---|---|---|\n");
+        for lint in &self.lints {
+            writeln!(
+                md,
+                "| {} | {} | [{}{}]({}) | {} | {} | {} | {} |",
+                lint.severity,
+                lint.code,
+                lint.column,
+                lint.row,
+                lint.record_url,
+                escape(&lint.card),
+                lint.text.as_deref().map(escape).unwrap_or_default(),
+                escape(&lint.message),
+                lint.suggestion.as_deref().map(escape).unwrap_or_default(),
+            )
+            .unwrap();
+        }
+        md
+    }
+}
+
+impl Lint {
+    pub fn from_source_error(
+        error: &ParseSourceError,
+        layout: &SpreadsheetLayout,
+        config: &LintConfig,
+    ) -> Self {
+        let (code, text, message, suggestion) = match &error.kind {
+            ParseSourceErrorKind::UnknownDropSource(drops_from, suggestions) => (
+                LintCode::UnknownDropSource,
+                Some(drops_from.name.clone()),
+                unknown_source_message(&drops_from.name, suggestions),
+                Some(match suggestions.first() {
+                    Some(suggestion) => format!("Replace with \"{suggestion}\""),
+                    None => "Check the spelling and styling of the source".to_owned(),
+                }),
+            ),
+            ParseSourceErrorKind::ActsMustBeItalic(drops_from) => (
+                LintCode::ActsMustBeItalic,
+                Some(drops_from.name.clone()),
+                "Refers to acts, but its font style is not italic".to_owned(),
+                Some(format!("Make {} italic", drops_from.name)),
+            ),
+            ParseSourceErrorKind::SourceOrVerifyIsExpectedButEmpty => (
+                LintCode::SourceOrVerifyIsExpectedButEmpty,
+                None,
+                "Source or need-to-verify source is expected, but there is none".to_owned(),
+                Some("Add a source or a need-to-verify source, or lower the confidence".to_owned()),
+            ),
+            ParseSourceErrorKind::GreynoteDisabledButCardNotLegacy => (
+                LintCode::GreynoteDisabledButCardNotLegacy,
+                None,
+                "Greynote is Disabled, but this is not a legacy card".to_owned(),
+                Some("Remove the Disabled greynote".to_owned()),
+            ),
+            ParseSourceErrorKind::LegacyCardShouldBeMarkedAsDisabled => (
+                LintCode::LegacyCardShouldBeMarkedAsDisabled,
+                None,
+                "Card is legacy, but not marked as disabled".to_owned(),
+                Some("Set greynote to Drop disabled".to_owned()),
+            ),
+            ParseSourceErrorKind::ConfidenceNoneButHasSources => (
+                LintCode::ConfidenceNoneButHasSources,
+                None,
+                "Confidence is None, but sources are not empty".to_owned(),
+                Some("Raise the confidence or move the sources to the verify column".to_owned()),
+            ),
+            ParseSourceErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                area,
+                area_level,
+                card_min_level,
+            } => (
+                LintCode::AreaLevelIsLowerThanCardMinLevel,
+                Some(area.clone()),
+                format!(
+                    "Area level is {area_level}, but the card drops from level {card_min_level}"
+                ),
+                Some(format!("Remove {area} or check the drop level of the card")),
+            ),
+            ParseSourceErrorKind::SourceIsDoneAndVerifyAtSameTime(source) => (
+                LintCode::SourceIsDoneAndVerifyAtSameTime,
+                Some(source.to_string()),
+                "Source is done and verify at the same time".to_owned(),
+                Some(format!("Remove {source} from the verify column")),
+            ),
+        };
+
+        let column = error.column.column(&layout.columns);
+        Lint {
+            code,
+            severity: config.severity(code),
+            card: error.card.clone(),
+            row: error.record_id,
+            column,
+            text,
+            message,
+            suggestion,
+            record_url: layout.record_url(error.record_id, column),
+        }
+    }
+
+    pub fn from_dumb_error(
+        error: &ParseDumbError,
+        layout: &SpreadsheetLayout,
+        config: &LintConfig,
+    ) -> Self {
+        let (code, text, message, suggestion) = match &error.kind {
+            ParseDumbErrKind::Greynote(err) => (
+                LintCode::InvalidGreynote,
+                None,
+                format!("Could not parse greynote. {err}"),
+                None,
+            ),
+            ParseDumbErrKind::CardName(err) => (
+                LintCode::InvalidCardName,
+                match err {
+                    ParseCardNameError::CellValueIsNotStr(value) => Some(value.to_string()),
+                    ParseCardNameError::CardNameNotExists(name) => Some(name.clone()),
+                },
+                err.to_string(),
+                Some("Check the spelling of the card name".to_owned()),
+            ),
+            ParseDumbErrKind::Confidence(err) => (
+                LintCode::InvalidConfidence,
+                None,
+                format!("Could not parse confidence. {err}"),
+                None,
+            ),
+            ParseDumbErrKind::RemainingWork(err) => (
+                LintCode::InvalidRemainingWork,
+                None,
+                format!("Could not parse remaining work. {err}"),
+                None,
+            ),
+            ParseDumbErrKind::StyledCell(_, err) => (
+                LintCode::InvalidStyledCell,
+                None,
+                format!("Could not parse styled cell into chunks. {err}"),
+                None,
+            ),
+            ParseDumbErrKind::DuplicateDropSource(_, drops_from) => (
+                LintCode::DuplicateDropSource,
+                Some(drops_from.name.clone()),
+                "Duplicate drop source".to_owned(),
+                Some(format!("Remove the duplicate {}", drops_from.name)),
+            ),
+        };
+
+        let column = error.column().column(&layout.columns);
+        Lint {
+            code,
+            severity: config.severity(code),
+            card: error.card.clone(),
+            row: error.record_id,
+            column,
+            text,
+            message,
+            suggestion,
+            record_url: layout.record_url(error.record_id, column),
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse::DivcordColumn,
+        spreadsheet::rich::{DropsFrom, FontStyles},
+    };
+
+    fn drops_from(name: &str) -> DropsFrom {
+        DropsFrom {
+            name: name.to_owned(),
+            styles: FontStyles::default(),
+        }
+    }
+
+    #[test]
+    fn reports_lints() {
+        let errors = vec![
+            ParseRecordError::ParseDropSources(vec![
+                ParseSourceError {
+                    card: "Rain of Chaos".to_owned(),
+                    record_id: 10,
+                    column: DivcordColumn::Verify,
+                    kind: ParseSourceErrorKind::ActsMustBeItalic(drops_from("The Ledge")),
+                },
+                ParseSourceError {
+                    card: "Rain of Chaos".to_owned(),
+                    record_id: 10,
+                    column: DivcordColumn::Sources,
+                    kind: ParseSourceErrorKind::UnknownDropSource(
+                        drops_from("Dunes | Map"),
+                        vec!["Dunes Map".to_owned()],
+                    ),
+                },
+            ]),
+            ParseRecordError::ParseDumb(ParseDumbError::new(
+                5,
+                "The Doctor".to_owned(),
+                ParseDumbErrKind::DuplicateDropSource(
+                    DivcordColumn::Sources,
+                    drops_from("Kirac Missions"),
+                ),
+            )),
+        ];
+        let config = LintConfig::default().warn(LintCode::ActsMustBeItalic);
+        let report = LintReport::new(&errors, &SpreadsheetLayout::default(), &config);
+
+        let codes = report
+            .lints
+            .iter()
+            .map(|lint| lint.code)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            [
+                LintCode::DuplicateDropSource,
+                LintCode::UnknownDropSource,
+                LintCode::ActsMustBeItalic
+            ]
+        );
+        let acts = &report.lints[2];
+        assert_eq!(acts.severity, Severity::Warning);
+        assert_eq!(acts.column, Column::letter('I'));
+        assert!(acts.record_url.ends_with("range=I10"));
+        assert!(report.has_errors());
+
+        let md = report.to_markdown();
+        assert!(md.starts_with("2 errors, 1 warnings"));
+        assert!(md.contains("Dunes \\| Map"));
+        assert!(md.contains(r#"Unknown source "Dunes \| Map" — did you mean "Dunes Map"?"#));
+
+        let json = report.to_json().unwrap();
+        let parsed: LintReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+}
@@ -1,17 +1,20 @@
 //! Parse drop sources.
 
 use crate::dropsource::predefined::PredefinedSource;
+use crate::dropsource::suggest::suggest;
 use crate::dropsource::Source;
 use crate::spreadsheet::record::ParseDumbError;
-use crate::spreadsheet::rich::HexColor;
+use crate::spreadsheet::rich::{FontStyles, HexColor};
 use crate::spreadsheet::{
-    record::{Confidence, Dumb, GreyNote, Record},
+    layout::ColumnMapping,
+    record::{Confidence, Dumb, GreyNote, Record, TagHypothesis},
     rich::DropsFrom,
-    Spreadsheet,
+    Spreadsheet, SpreadsheetLayout,
 };
 use divi::IsCard;
 use crate::poe_data::act::ActArea;
 use crate::poe_data::PoeData;
+use googlesheets::Column;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fmt::Display;
@@ -131,6 +134,7 @@ pub fn parse_record(dumb: Dumb, poe_data: &PoeData) -> ParseRecordResult {
             errors.push(ParseSourceError {
                 card: dumb.card.clone(),
                 record_id: dumb.id,
+                column: DivcordColumn::Verify,
                 kind: ParseSourceErrorKind::SourceIsDoneAndVerifyAtSameTime(
                     s.to_owned().to_owned(),
                 ),
@@ -151,10 +155,34 @@ pub fn parse_record(dumb: Dumb, poe_data: &PoeData) -> ParseRecordResult {
             .map(ParseSourceError::from),
     );
 
+    let hypothesis = dumb
+        .tag_hypothesis
+        .as_deref()
+        .map(|text| TagHypothesis::parse(text, &dumb, poe_data));
+
+    let (datamined_sources, errors_datamined_drops_from) =
+        parse_dropses_from(&dumb, poe_data, SourcesKind::Datamined);
+    errors.extend(
+        errors_datamined_drops_from
+            .into_iter()
+            .map(ParseSourceError::from),
+    );
+
+    errors.extend(check_map_levels(
+        &dumb,
+        poe_data,
+        [
+            (&sources, DivcordColumn::Sources),
+            (&verify_sources, DivcordColumn::Verify),
+            (&datamined_sources, DivcordColumn::Datamined),
+        ],
+    ));
+
     ParseRecordResult {
         record: Record {
             sources,
             verify_sources,
+            datamined_sources,
             id: dumb.id,
             greynote: dumb.greynote,
             card: dumb.card,
@@ -162,27 +190,68 @@ pub fn parse_record(dumb: Dumb, poe_data: &PoeData) -> ParseRecordResult {
             confidence: dumb.confidence,
             remaining_work: dumb.remaining_work,
             notes: dumb.notes,
+            hypothesis,
         },
         errors,
     }
 }
 
+/// Maps, that are below the card drop level. Act areas are checked while parsing.
+fn check_map_levels(
+    dumb: &Dumb,
+    poe_data: &PoeData,
+    columns: [(&Vec<Source>, DivcordColumn); 3],
+) -> Vec<ParseSourceError> {
+    let Some(card_min_level) = poe_data.cards.dict.get(&dumb.card).map(|c| c.min_level) else {
+        return vec![];
+    };
+
+    columns
+        .into_iter()
+        .flat_map(|(sources, column)| sources.iter().map(move |source| (source, column)))
+        .filter_map(|(source, column)| {
+            let Source::Map(name) = source else {
+                return None;
+            };
+            let map = poe_data.maps.iter().find(|map| &map.name == name)?;
+            (map.level() < card_min_level).then(|| ParseSourceError {
+                card: dumb.card.clone(),
+                record_id: dumb.id,
+                column,
+                kind: ParseSourceErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                    area: name.clone(),
+                    area_level: map.level(),
+                    card_min_level,
+                },
+            })
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct ParseSourceError {
     pub card: String,
     pub record_id: usize,
+    pub column: DivcordColumn,
     pub kind: ParseSourceErrorKind,
 }
 
 #[derive(Debug)]
 pub enum ParseSourceErrorKind {
-    UnknownDropSource(DropsFrom),
+    /// Unknown drops with the closest known source names.
+    UnknownDropSource(DropsFrom, Vec<String>),
     ActsMustBeItalic(DropsFrom),
     SourceOrVerifyIsExpectedButEmpty,
     GreynoteDisabledButCardNotLegacy,
     LegacyCardShouldBeMarkedAsDisabled,
     ConfidenceNoneButHasSources,
     SourceIsDoneAndVerifyAtSameTime(Source),
+    /// Act area or map is below the card drop level.
+    AreaLevelIsLowerThanCardMinLevel {
+        area: String,
+        area_level: u32,
+        card_min_level: u32,
+    },
 }
 
 impl From<ParseDropsFromError> for ParseSourceError {
@@ -190,13 +259,22 @@ impl From<ParseDropsFromError> for ParseSourceError {
         ParseSourceError {
             card: value.card,
             record_id: value.record_id,
+            column: value.sources_kind.column(),
             kind: match value.kind {
-                ParseDropsFromErrorKind::Unknown => {
-                    ParseSourceErrorKind::UnknownDropSource(value.drops_from)
+                ParseDropsFromErrorKind::Unknown { suggestions } => {
+                    ParseSourceErrorKind::UnknownDropSource(value.drops_from, suggestions)
                 }
                 ParseDropsFromErrorKind::ActsMustBeItalic => {
                     ParseSourceErrorKind::ActsMustBeItalic(value.drops_from)
                 }
+                ParseDropsFromErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                    area_level,
+                    card_min_level,
+                } => ParseSourceErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                    area: value.drops_from.name,
+                    area_level,
+                    card_min_level,
+                },
             },
         }
     }
@@ -211,52 +289,72 @@ pub struct UnknownDropsFrom {
 
 impl Display for ParseSourceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message(&SpreadsheetLayout::default()))
+    }
+}
+
+impl ParseSourceError {
+    /// Error message with the link to the cell in the sheet of the layout.
+    pub fn message(&self, layout: &SpreadsheetLayout) -> String {
         let ParseSourceError {
             card,
             record_id,
+            column,
             kind,
         } = self;
+        let url = layout.record_url(*record_id, column.column(&layout.columns));
         match kind {
-            ParseSourceErrorKind::UnknownDropSource(drops_from) => write!(
-                                f,
-                                "{record_id}.{card}. Unknown variant of card source {}. {}",
-                                drops_from.name,
-                                record_url(*record_id, DivcordColumn::Sources)
-                            ),
-            ParseSourceErrorKind::ActsMustBeItalic(drops_from) => write!(
-                                f,
-                                "{record_id}.{card}. Spreadsheet styling error: If {} refers to acts, it's font-style must be italic. {}",
-                                drops_from.name,
-                                record_url(*record_id, DivcordColumn::Sources)
-                            ),
-            ParseSourceErrorKind::SourceOrVerifyIsExpectedButEmpty => write!(
-                                f,
-                                "{record_id}.{card}. Source or need-to-verify source is expected, but there is none. {}",
-                                record_url(*record_id, DivcordColumn::Sources)
-                            ),
-            ParseSourceErrorKind::GreynoteDisabledButCardNotLegacy => write!(
-                                f,
-                                "{record_id}. Card {card} has greynote Disabled, but this is not a legacy card {}",
-                                record_url(*record_id, DivcordColumn::GreyNote)
-                            ),
-            ParseSourceErrorKind::LegacyCardShouldBeMarkedAsDisabled => write!(
-                                f,
-                                "{record_id}. Card {card} is legacy, but not marked as disabled. {}",
-                                record_url(*record_id, DivcordColumn::GreyNote)
-                            ),
-            ParseSourceErrorKind::ConfidenceNoneButHasSources => write!(
-                                f,
-                                "{record_id}.{card}. Confidence is None, but sources not empty {}",
-                                record_url(*record_id, DivcordColumn::Sources)
-                            ),
-            ParseSourceErrorKind::SourceIsDoneAndVerifyAtSameTime(source) => write!(
-                                f, "{record_id}.{card}. Source {source:?} is done and verify at same time {}", 
-                                record_url(*record_id, DivcordColumn::Sources)
-                            ),
+            ParseSourceErrorKind::UnknownDropSource(drops_from, suggestions) => format!(
+                "{record_id}.{card}. {}. {url}",
+                unknown_source_message(&drops_from.name, suggestions)
+            ),
+            ParseSourceErrorKind::ActsMustBeItalic(drops_from) => format!(
+                "{record_id}.{card}. Spreadsheet styling error: If {} refers to acts, it's font-style must be italic. {url}",
+                drops_from.name
+            ),
+            ParseSourceErrorKind::SourceOrVerifyIsExpectedButEmpty => format!(
+                "{record_id}.{card}. Source or need-to-verify source is expected, but there is none. {url}"
+            ),
+            ParseSourceErrorKind::GreynoteDisabledButCardNotLegacy => format!(
+                "{record_id}. Card {card} has greynote Disabled, but this is not a legacy card {url}"
+            ),
+            ParseSourceErrorKind::LegacyCardShouldBeMarkedAsDisabled => format!(
+                "{record_id}. Card {card} is legacy, but not marked as disabled. {url}"
+            ),
+            ParseSourceErrorKind::ConfidenceNoneButHasSources => format!(
+                "{record_id}.{card}. Confidence is None, but sources not empty {url}"
+            ),
+            ParseSourceErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                area,
+                area_level,
+                card_min_level,
+            } => format!(
+                "{record_id}.{card}. Level of {area} is {area_level}, but the card drops from level {card_min_level}. {url}"
+            ),
+            ParseSourceErrorKind::SourceIsDoneAndVerifyAtSameTime(source) => format!(
+                "{record_id}.{card}. Source {source:?} is done and verify at same time {url}"
+            ),
         }
     }
 }
 
+/// `Unknown source "Cortx" — did you mean "Cortex"?`
+pub fn unknown_source_message(name: &str, suggestions: &[String]) -> String {
+    let quoted = suggestions
+        .iter()
+        .map(|suggestion| format!("\"{suggestion}\""))
+        .collect::<Vec<_>>();
+    match quoted.split_last() {
+        None => format!("Unknown source \"{name}\""),
+        Some((last, [])) => format!("Unknown source \"{name}\" — did you mean {last}?"),
+        Some((last, rest)) => format!(
+            "Unknown source \"{name}\" — did you mean {} or {last}?",
+            rest.join(", ")
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DivcordColumn {
     GreyNote,
     Card,
@@ -264,27 +362,38 @@ pub enum DivcordColumn {
     Confidence,
     RemainingWork,
     Sources,
+    Datamined,
     Verify,
     Notes,
 }
 
 impl DivcordColumn {
-    pub fn letter(&self) -> char {
+    /// Column in the given layout.
+    pub fn column(&self, columns: &ColumnMapping) -> Column {
         match self {
-            DivcordColumn::GreyNote => 'A',
-            DivcordColumn::Card => 'B',
-            DivcordColumn::TagHypothesis => 'C',
-            DivcordColumn::Confidence => 'D',
-            DivcordColumn::RemainingWork => 'F',
-            DivcordColumn::Sources => 'G',
-            DivcordColumn::Verify => 'H',
-            DivcordColumn::Notes => 'I',
+            DivcordColumn::GreyNote => columns.greynote,
+            DivcordColumn::Card => columns.card,
+            DivcordColumn::TagHypothesis => columns.tag_hypothesis,
+            DivcordColumn::Confidence => columns.confidence,
+            DivcordColumn::RemainingWork => columns.remaining_work,
+            DivcordColumn::Sources => columns.drops,
+            DivcordColumn::Datamined => columns.drops_datamined,
+            DivcordColumn::Verify => columns.drops_verify,
+            DivcordColumn::Notes => columns.notes,
         }
     }
+
+    #[deprecated(note = "columns can move, use `DivcordColumn::column` with the layout")]
+    pub fn letter(&self) -> char {
+        let column = self.column(&SpreadsheetLayout::default().columns);
+        column.to_string().chars().next().unwrap_or('A')
+    }
 }
 
+#[deprecated(note = "use `SpreadsheetLayout::record_url`")]
 pub fn record_url(id: usize, column: DivcordColumn) -> String {
-    format!("https://docs.google.com/spreadsheets/d/1Pf2KNuGguZLyf6eu_R0E503U0QNyfMZqaRETsN5g6kU/edit?pli=1#gid=0&range={}{id}", column.letter())
+    let layout = SpreadsheetLayout::default();
+    layout.record_url(id, column.column(&layout.columns))
 }
 
 pub fn parse_record_dropsources(
@@ -296,6 +405,7 @@ pub fn parse_record_dropsources(
         let err = ParseSourceError {
             record_id: dumb.id,
             card: dumb.card.to_owned(),
+            column: DivcordColumn::GreyNote,
             kind: ParseSourceErrorKind::LegacyCardShouldBeMarkedAsDisabled,
         };
         return (vec![Source::disabled()], vec![err]);
@@ -310,6 +420,7 @@ pub fn parse_record_dropsources(
         errors.push(ParseSourceError {
             record_id: dumb.id,
             card: dumb.card.to_owned(),
+            column: DivcordColumn::GreyNote,
             kind: ParseSourceErrorKind::GreynoteDisabledButCardNotLegacy,
         });
     }
@@ -323,6 +434,7 @@ pub fn parse_record_dropsources(
         errors.push(ParseSourceError {
             record_id: dumb.id,
             card: dumb.card.to_owned(),
+            column: DivcordColumn::Confidence,
             kind: ParseSourceErrorKind::ConfidenceNoneButHasSources,
         });
     }
@@ -336,6 +448,7 @@ pub fn parse_record_dropsources(
         errors.push(ParseSourceError {
             record_id: dumb.id,
             card: dumb.card.to_owned(),
+            column: DivcordColumn::Sources,
             kind: ParseSourceErrorKind::SourceOrVerifyIsExpectedButEmpty,
         });
     }
@@ -343,10 +456,21 @@ pub fn parse_record_dropsources(
     (sources, errors)
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum SourcesKind {
     Source,
     Verify,
+    Datamined,
+}
+
+impl SourcesKind {
+    pub fn column(&self) -> DivcordColumn {
+        match self {
+            SourcesKind::Source => DivcordColumn::Sources,
+            SourcesKind::Verify => DivcordColumn::Verify,
+            SourcesKind::Datamined => DivcordColumn::Datamined,
+        }
+    }
 }
 
 /// Parses all instances of record's drops_from and collects it into one Vec<Source>
@@ -360,10 +484,11 @@ pub fn parse_dropses_from(
     let drops_to_parse = match column {
         SourcesKind::Source => &dumb.drops,
         SourcesKind::Verify => &dumb.drops_to_verify,
+        SourcesKind::Datamined => &dumb.drops_datamined,
     };
 
     for d in drops_to_parse {
-        match parse_one_drops_from(d, dumb, poe_data) {
+        match parse_one_drops_from(d, dumb, poe_data, column) {
             Ok(inner_sources) => sources.extend(inner_sources),
             Err(err) => errors.push(err),
         };
@@ -374,12 +499,15 @@ pub fn parse_dropses_from(
 
 #[derive(Debug, PartialEq)]
 pub enum ParseDropsFromErrorKind {
-    Unknown,
-    ActsMustBeItalic, // DropSourceLevelisLowerThanCardMinLevel {
-                      //     level: u32,
-                      //     card: String,
-                      //     card_min_drop_level: u32,
-                      // },
+    Unknown {
+        suggestions: Vec<String>,
+    },
+    ActsMustBeItalic,
+    /// Act area is known, but its level is lower than the card drop level.
+    AreaLevelIsLowerThanCardMinLevel {
+        area_level: u32,
+        card_min_level: u32,
+    },
 }
 
 #[derive(Debug)]
@@ -387,19 +515,55 @@ pub struct ParseDropsFromError {
     pub card: String,
     pub record_id: usize,
     pub drops_from: DropsFrom,
+    /// Column of the drops.
+    pub sources_kind: SourcesKind,
     pub kind: ParseDropsFromErrorKind,
 }
 
 pub fn parse_one_drops_from(
     d: &DropsFrom,
     dumb: &Dumb,
-    PoeData {
+    poe_data: &PoeData,
+    sources_kind: SourcesKind,
+) -> Result<Vec<Source>, ParseDropsFromError> {
+    resolve_drops_from(d, dumb, poe_data, sources_kind).map_err(|mut err| {
+        if let ParseDropsFromErrorKind::Unknown { suggestions } = &mut err.kind {
+            *suggestions = suggest(&strip_comment(&d.name), poe_data);
+        }
+        err
+    })
+}
+
+/// Resolves a name without spreadsheet styling, as a regular or as an italic (act) drop source.
+pub fn resolve_source(name: &str, dumb: &Dumb, poe_data: &PoeData) -> Option<Vec<Source>> {
+    [false, true].into_iter().find_map(|italic| {
+        let d = DropsFrom {
+            name: name.to_owned(),
+            styles: FontStyles {
+                italic,
+                ..Default::default()
+            },
+        };
+        resolve_drops_from(&d, dumb, poe_data, SourcesKind::Source)
+            .ok()
+            .filter(|sources| !sources.is_empty())
+    })
+}
+
+/// [`parse_one_drops_from`] without suggestions for unknown sources.
+fn resolve_drops_from(
+    d: &DropsFrom,
+    dumb: &Dumb,
+    poe_data: &PoeData,
+    sources_kind: SourcesKind,
+) -> Result<Vec<Source>, ParseDropsFromError> {
+    let PoeData {
         acts,
         cards,
         maps,
         mapbosses,
-    }: &PoeData,
-) -> Result<Vec<Source>, ParseDropsFromError> {
+        ..
+    } = poe_data;
     if d.styles.strikethrough {
         return Ok(vec![]);
     }
@@ -408,7 +572,8 @@ pub fn parse_one_drops_from(
         return Ok(vec![Source::Predefined(predefined_source)]);
     }
 
-    let card_min_drop_level = cards.card(&dumb.card).min_level;
+    // Cards of a new league may be missing from poe data, their areas are not filtered by level
+    let card_min_drop_level = cards.dict.get(&dumb.card).map_or(0, |card| card.min_level);
     // Acts + bosses
     if d.styles.italic {
         if let Some(sources) =
@@ -443,15 +608,38 @@ pub fn parse_one_drops_from(
             card: dumb.card.to_owned(),
             record_id: dumb.id,
             drops_from: d.to_owned(),
+            sources_kind,
             kind: ParseDropsFromErrorKind::ActsMustBeItalic,
         });
     }
 
+    // Act areas, that were filtered out by the card drop level
+    if let Some(area_level) = acts::parse_act_areas(d, acts, 0)
+        .iter()
+        .filter_map(|id| poe_data.act_area_id(id))
+        .map(|act_area| u32::from(act_area.area_level))
+        .max()
+    {
+        return Err(ParseDropsFromError {
+            card: dumb.card.to_owned(),
+            record_id: dumb.id,
+            drops_from: d.to_owned(),
+            sources_kind,
+            kind: ParseDropsFromErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                area_level,
+                card_min_level: card_min_drop_level,
+            },
+        });
+    }
+
     Err(ParseDropsFromError {
         card: dumb.card.to_owned(),
         record_id: dumb.id,
         drops_from: d.to_owned(),
-        kind: ParseDropsFromErrorKind::Unknown,
+        sources_kind,
+        kind: ParseDropsFromErrorKind::Unknown {
+            suggestions: vec![],
+        },
     })
 }
 
@@ -687,3 +875,90 @@ mod acts {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poe_data::{
+        act::ActAreaId,
+        cards::{Card, CardsData},
+        maps::Map,
+    };
+
+    fn poe_data(card_min_level: u32) -> PoeData {
+        PoeData {
+            acts: vec![ActArea::test("1_1_2", "The Coast", 1, 2)],
+            cards: CardsData::test([Card::test("Some Card", card_min_level)]),
+            maps: vec![Map::test("Dunes Map")],
+            ..Default::default()
+        }
+    }
+
+    fn dumb(drops: DropsFrom) -> Dumb {
+        Dumb {
+            drops: vec![drops],
+            ..Dumb::test("Some Card")
+        }
+    }
+
+    fn drops_from(name: &str, italic: bool) -> DropsFrom {
+        DropsFrom {
+            name: name.to_owned(),
+            styles: FontStyles {
+                italic,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn reports_map_below_card_level() {
+        let result = parse_record(dumb(drops_from("Dunes Map", false)), &poe_data(70));
+        assert_eq!(result.record.sources, [Source::Map("Dunes Map".to_owned())]);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].column, DivcordColumn::Sources);
+        assert!(matches!(
+            &result.errors[0].kind,
+            ParseSourceErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                area,
+                area_level: 68,
+                card_min_level: 70,
+            } if area == "Dunes Map"
+        ));
+
+        let result = parse_record(dumb(drops_from("Dunes Map", false)), &poe_data(68));
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn parses_hypothesis_of_card_missing_from_poe_data() {
+        let dumb = Dumb {
+            tag_hypothesis: Some("boss only".to_owned()),
+            ..Dumb::test("Some Card")
+        };
+        let result = parse_record(dumb, &PoeData::default());
+        let hypothesis = result.record.hypothesis.unwrap();
+        assert_eq!(hypothesis.text, ["boss only"]);
+    }
+
+    #[test]
+    fn reports_act_area_below_card_level() {
+        let result = parse_record(dumb(drops_from("The Coast", true)), &poe_data(10));
+        assert!(result.record.sources.is_empty());
+        assert!(result.errors.iter().any(|err| matches!(
+            &err.kind,
+            ParseSourceErrorKind::AreaLevelIsLowerThanCardMinLevel {
+                area,
+                area_level: 2,
+                card_min_level: 10,
+            } if area == "The Coast"
+        )));
+
+        let result = parse_record(dumb(drops_from("The Coast", true)), &poe_data(0));
+        assert_eq!(
+            result.record.sources,
+            [Source::Act(ActAreaId::new("1_1_2".to_owned()))]
+        );
+        assert!(result.errors.is_empty());
+    }
+}
@@ -5,6 +5,7 @@ pub mod maps;
 pub mod mapbosses;
 
 use self::{act::ActArea, cards::CardsData, mapbosses::MapBoss, maps::Map};
+use crate::dropsource::{predefined::extra::ExtraSource, suggest::KnownNames};
 use act::ActAreaId;
 use serde::{Deserialize, Serialize};
 
@@ -14,9 +15,21 @@ pub struct PoeData {
     pub cards: CardsData,
     pub maps: Vec<Map>,
     pub mapbosses: Vec<MapBoss>,
+    /// Sources of new league content, see [`PoeData::register_extra_sources`].
+    #[serde(default)]
+    pub extra_sources: Vec<ExtraSource>,
+    /// Names for "did you mean" suggestions, see [`crate::dropsource::suggest`].
+    #[serde(skip)]
+    pub known_names: KnownNames,
 }
 
 impl PoeData {
+    /// Registers [`PoeData::extra_sources`]. Call it before parsing or deserializing records,
+    /// that have these sources.
+    pub fn register_extra_sources(&self) {
+        crate::dropsource::predefined::extra::register(self.extra_sources.iter().cloned());
+    }
+
     pub fn mapboss(&self, name: &str) -> Option<&MapBoss> {
         self.mapbosses
             .iter()
@@ -1,7 +1,10 @@
 #![cfg(feature = "fs_cache_fetcher")]
 
-use super::Spreadsheet;
-pub use fs_cache_fetcher::{Config, DataFetcher, Stale};
+use super::{Spreadsheet, SHEET_TITLE, SPREADSHEET_ID};
+pub use fs_cache_fetcher::{
+    Compression, Config, DataFetcher, FsStorage, Retention, ServeStale, Stale,
+};
+use std::sync::Arc;
 
 pub struct SpreadsheetFetcher(pub Config);
 
@@ -9,8 +12,12 @@ impl Default for SpreadsheetFetcher {
     fn default() -> Self {
         Self(Config {
             save: true,
-            filename: "spreadsheet.json",
+            filename: "spreadsheet.json.gz".to_owned(),
             stale: Stale::ReloadEveryTime,
+            storage: Arc::new(FsStorage::project_data()),
+            serve_stale: ServeStale::OnError,
+            compression: Compression::Gzip,
+            ..Config::default()
         })
     }
 }
@@ -28,6 +35,9 @@ impl DataFetcher for SpreadsheetFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.0
     }
+    fn source(&self) -> Option<String> {
+        Some(format!("{SPREADSHEET_ID}/{SHEET_TITLE}"))
+    }
 }
 
 #[derive(Debug)]
@@ -35,6 +45,7 @@ pub enum FetcherError {
     Serde(serde_json::Error),
     Io(std::io::Error),
     Reqwest(reqwest::Error),
+    Google(googlesheets::error::Error),
 }
 
 impl From<serde_json::Error> for FetcherError {
@@ -54,3 +65,9 @@ impl From<reqwest::Error> for FetcherError {
         FetcherError::Reqwest(value)
     }
 }
+
+impl From<googlesheets::error::Error> for FetcherError {
+    fn from(value: googlesheets::error::Error) -> Self {
+        FetcherError::Google(value)
+    }
+}
@@ -0,0 +1,334 @@
+//! Where the data lives in the spreadsheet: id, sheet, header row and columns of the record fields.
+//!
+//! Columns can be supplied explicitly or detected from the header row,
+//! so moved columns do not break fetching and parsing.
+
+use googlesheets::Column;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const SPREADSHEET_ID: &str = "1Pf2KNuGguZLyf6eu_R0E503U0QNyfMZqaRETsN5g6kU";
+pub const SHEET_TITLE: &str = "Cards_and_Hypotheses";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpreadsheetLayout {
+    pub spreadsheet_id: String,
+    pub sheet: String,
+    /// 1-based row of column headers. Records start on the next row.
+    pub header_row: u32,
+    pub columns: ColumnMapping,
+}
+
+impl Default for SpreadsheetLayout {
+    fn default() -> Self {
+        SpreadsheetLayout {
+            spreadsheet_id: SPREADSHEET_ID.to_owned(),
+            sheet: SHEET_TITLE.to_owned(),
+            header_row: 2,
+            columns: ColumnMapping::default(),
+        }
+    }
+}
+
+impl SpreadsheetLayout {
+    /// 1-based row of the first record.
+    pub const fn first_record_row(&self) -> u32 {
+        self.header_row + 1
+    }
+
+    /// Record id is the sheet row of the record.
+    pub fn record_id(&self, row_index: usize) -> usize {
+        row_index + self.first_record_row() as usize
+    }
+
+    /// Deep link to the cell of the record.
+    pub fn record_url(&self, record_id: usize, column: Column) -> String {
+        format!(
+            "https://docs.google.com/spreadsheets/d/{}/edit?pli=1#gid=0&range={column}{record_id}",
+            self.spreadsheet_id
+        )
+    }
+
+    /// Finds columns by the header row. Columns, that are not found, keep their letters
+    /// and their names are returned.
+    ///
+    /// If a kept letter points at a column detected for another field, the header can not be
+    /// trusted: all columns keep their letters and all names are returned.
+    pub fn detect_columns(&mut self, header: &[Value]) -> Vec<&'static str> {
+        let layout_columns = self.columns.clone();
+        let headers: Vec<String> = header
+            .iter()
+            .map(|value| normalize(value.as_str().unwrap_or_default()))
+            .collect();
+        let mut taken = vec![false; headers.len()];
+        let mut not_found = vec![];
+
+        for (field, column) in self.columns.fields_mut() {
+            let found = find_by(&headers, &taken, |header| field.aliases.contains(&header))
+                .or_else(|| {
+                    find_by(&headers, &taken, |header| {
+                        field
+                            .keywords
+                            .iter()
+                            .any(|keyword| header.contains(keyword))
+                            && !field.excluded.iter().any(|word| header.contains(word))
+                    })
+                });
+            match found {
+                Some(index) => {
+                    taken[index] = true;
+                    *column = Column::new(index as u32);
+                }
+                None => not_found.push((field.name, *column)),
+            }
+        }
+
+        let collides = not_found
+            .iter()
+            .any(|(_, column)| taken.get(column.index() as usize) == Some(&true));
+        if collides {
+            self.columns = layout_columns;
+            return self
+                .columns
+                .fields_mut()
+                .map(|(field, _)| field.name)
+                .into();
+        }
+
+        not_found.into_iter().map(|(name, _)| name).collect()
+    }
+}
+
+/// Columns of the record fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnMapping {
+    pub greynote: Column,
+    pub card: Column,
+    pub tag_hypothesis: Column,
+    pub confidence: Column,
+    pub remaining_work: Column,
+    /// "non-atlas locations"
+    pub drops: Column,
+    pub drops_datamined: Column,
+    /// "To Confirm or Verify"
+    pub drops_verify: Column,
+    pub notes: Column,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping {
+            greynote: Column::letter('A'),
+            card: Column::letter('B'),
+            tag_hypothesis: Column::letter('C'),
+            confidence: Column::letter('D'),
+            remaining_work: Column::letter('F'),
+            drops: Column::letter('G'),
+            drops_datamined: Column::letter('H'),
+            drops_verify: Column::letter('I'),
+            notes: Column::letter('J'),
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// The rightmost column.
+    pub fn last(&self) -> Column {
+        [
+            self.greynote,
+            self.card,
+            self.tag_hypothesis,
+            self.confidence,
+            self.remaining_work,
+            self.drops,
+            self.drops_datamined,
+            self.drops_verify,
+            self.notes,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(Column::A)
+    }
+
+    /// Cell of the row, `Null` if the row is shorter.
+    pub fn cell(row: &[Value], column: Column) -> &Value {
+        static NULL: Value = Value::Null;
+        row.get(column.index() as usize).unwrap_or(&NULL)
+    }
+
+    /// Fields in the order of detection: the more specific headers go first,
+    /// so "Old Confidence" or "Datamined drops" are not taken for more generic ones.
+    fn fields_mut(&mut self) -> [(Field, &mut Column); 9] {
+        [
+            (GREYNOTE, &mut self.greynote),
+            (DROPS_DATAMINED, &mut self.drops_datamined),
+            (DROPS_VERIFY, &mut self.drops_verify),
+            (CONFIDENCE, &mut self.confidence),
+            (REMAINING_WORK, &mut self.remaining_work),
+            (TAG_HYPOTHESIS, &mut self.tag_hypothesis),
+            (NOTES, &mut self.notes),
+            (DROPS, &mut self.drops),
+            (CARD, &mut self.card),
+        ]
+    }
+}
+
+/// Header names of a field: exact aliases are preferred over keywords.
+struct Field {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    keywords: &'static [&'static str],
+    excluded: &'static [&'static str],
+}
+
+const GREYNOTE: Field = Field {
+    name: "greynote",
+    aliases: &["greynote", "grey note"],
+    keywords: &["greynote", "grey note"],
+    excluded: &[],
+};
+const CARD: Field = Field {
+    name: "card",
+    aliases: &["card", "cards", "card name"],
+    keywords: &["card"],
+    excluded: &[],
+};
+const TAG_HYPOTHESIS: Field = Field {
+    name: "tag hypothesis",
+    aliases: &["tag hypothesis", "tag"],
+    keywords: &["hypothesis"],
+    excluded: &[],
+};
+const CONFIDENCE: Field = Field {
+    name: "confidence",
+    aliases: &["confidence"],
+    keywords: &["confidence"],
+    excluded: &["old"],
+};
+const REMAINING_WORK: Field = Field {
+    name: "remaining work",
+    aliases: &["remaining work"],
+    keywords: &["remaining"],
+    excluded: &[],
+};
+const DROPS: Field = Field {
+    name: "drops",
+    aliases: &["drops", "non-atlas locations"],
+    keywords: &["non-atlas", "drop source", "drops"],
+    excluded: &["datamine", "verify", "confirm"],
+};
+const DROPS_DATAMINED: Field = Field {
+    name: "datamined drops",
+    aliases: &["datamined", "datamined drops"],
+    keywords: &["datamine"],
+    excluded: &[],
+};
+const DROPS_VERIFY: Field = Field {
+    name: "drops to verify",
+    aliases: &["to confirm or verify", "verify"],
+    keywords: &["verify", "confirm"],
+    excluded: &[],
+};
+const NOTES: Field = Field {
+    name: "notes",
+    aliases: &["notes", "note"],
+    keywords: &["note"],
+    excluded: &["grey"],
+};
+
+fn normalize(header: &str) -> String {
+    header
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn find_by(headers: &[String], taken: &[bool], matches: impl Fn(&str) -> bool) -> Option<usize> {
+    headers
+        .iter()
+        .enumerate()
+        .find(|(index, header)| !taken[*index] && !header.is_empty() && matches(header))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_moved_columns() {
+        let mut layout = SpreadsheetLayout::default();
+        let not_found = layout.detect_columns(&[
+            json!("Greynote"),
+            json!("Card"),
+            json!("Tag Hypothesis"),
+            json!("Old Confidence"),
+            json!("Confidence"),
+            json!("Remaining Work"),
+            json!("Notes"),
+            json!("Drops from non-atlas locations"),
+            json!("Datamined drops"),
+            json!(" To Confirm or\nVerify "),
+        ]);
+
+        assert!(not_found.is_empty());
+        let columns = &layout.columns;
+        assert_eq!(columns.confidence, Column::letter('E'));
+        assert_eq!(columns.notes, Column::letter('G'));
+        assert_eq!(columns.drops, Column::letter('H'));
+        assert_eq!(columns.drops_datamined, Column::letter('I'));
+        assert_eq!(columns.drops_verify, Column::letter('J'));
+        assert_eq!(columns.last(), Column::letter('J'));
+    }
+
+    #[test]
+    fn keeps_not_found_columns() {
+        let mut layout = SpreadsheetLayout::default();
+        let not_found = layout.detect_columns(&[json!("Card"), json!("Greynote")]);
+        assert_eq!(layout.columns.card, Column::A);
+        assert_eq!(layout.columns.greynote, Column::letter('B'));
+        assert_eq!(layout.columns.drops, Column::letter('G'));
+        assert!(not_found.contains(&"drops"));
+    }
+
+    #[test]
+    fn keeps_layout_if_not_found_column_is_taken() {
+        let mut layout = SpreadsheetLayout::default();
+        // Confidence header is renamed, its letter D is detected as notes
+        let not_found = layout.detect_columns(&[
+            json!("Greynote"),
+            json!("Card"),
+            json!("Tag Hypothesis"),
+            json!("Notes"),
+            json!(""),
+            json!("Remaining Work"),
+            json!("Drops"),
+            json!("Datamined"),
+            json!("Verify"),
+        ]);
+        assert_eq!(layout.columns, ColumnMapping::default());
+        assert_eq!(not_found.len(), 9);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_letters_match_default_layout() {
+        use crate::spreadsheet::{
+            DROPS_COLUMN_LETTER, DROPS_DATAMINED_COLUMN_LETTER, DROPS_VERIFY_COLUMN_LETTER,
+        };
+        let columns = ColumnMapping::default();
+        assert_eq!(Column::letter(DROPS_COLUMN_LETTER), columns.drops);
+        assert_eq!(
+            Column::letter(DROPS_DATAMINED_COLUMN_LETTER),
+            columns.drops_datamined
+        );
+        assert_eq!(
+            Column::letter(DROPS_VERIFY_COLUMN_LETTER),
+            columns.drops_verify
+        );
+    }
+}
@@ -1,10 +1,16 @@
 //! Defines and loads [Divcord Spreadsheet](https://docs.google.com/spreadsheets/d/1Pf2KNuGguZLyf6eu_R0E503U0QNyfMZqaRETsN5g6kU/edit?pli=1#gid=0)
 
 pub mod fs_cache_fetcher;
+pub mod layout;
 pub mod record;
 pub mod rich;
+pub mod write;
+pub mod xlsx;
 
-pub use fetch::fetch_spreadsheet;
+pub use fetch::{
+    fetch_spreadsheet, fetch_spreadsheet_with, fetch_spreadsheet_with_layout, ColumnsFrom,
+};
+pub use layout::{ColumnMapping, SpreadsheetLayout, SHEET_TITLE, SPREADSHEET_ID};
 
 #[allow(unused_imports)]
 use self::rich::Cell;
@@ -13,18 +19,28 @@ use googlesheets::sheet::ValueRange;
 use record::ParseDumbError;
 use serde::{Deserialize, Serialize};
 
+#[deprecated(note = "columns can move, use `SpreadsheetLayout::default().columns.drops`")]
 pub const DROPS_COLUMN_LETTER: char = 'G';
+#[deprecated(note = "columns can move, use `SpreadsheetLayout::default().columns.drops_datamined`")]
 pub const DROPS_DATAMINED_COLUMN_LETTER: char = 'H';
+#[deprecated(note = "columns can move, use `SpreadsheetLayout::default().columns.drops_verify`")]
 pub const DROPS_VERIFY_COLUMN_LETTER: char = 'I';
 
 /// [Divcord Spreadsheet](https://docs.google.com/spreadsheets/d/1Pf2KNuGguZLyf6eu_R0E503U0QNyfMZqaRETsN5g6kU/edit?pli=1#gid=0)
 /// This simple struct consist of api results:
 /// - whole sheet in simplest possible form
 /// - font styles data for sources column
+/// - layout the data was fetched with
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Spreadsheet {
     pub sheet: ValueRange,
     pub styled_columns: StyledDropsColumns,
+    #[serde(default)]
+    pub layout: SpreadsheetLayout,
+    /// Fields, whose columns were not found in the header row, see [`ColumnsFrom::HeaderRow`].
+    /// These are read from the letters of the layout, check them before trusting the records.
+    #[serde(default)]
+    pub missing_columns: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -36,14 +52,21 @@ pub struct StyledDropsColumns {
 
 pub struct StyledDropsCells<'a> {
     drops: &'a Cell,
+    drops_datamined: &'a Cell,
     drops_verify: &'a Cell,
 }
 
 impl Spreadsheet {
-    pub const fn new(sheet: ValueRange, styled_columns: StyledDropsColumns) -> Self {
+    pub fn new(
+        sheet: ValueRange,
+        styled_columns: StyledDropsColumns,
+        layout: SpreadsheetLayout,
+    ) -> Self {
         Self {
             sheet,
             styled_columns,
+            layout,
+            missing_columns: vec![],
         }
     }
 
@@ -56,10 +79,27 @@ impl Spreadsheet {
     }
 
     /// Fetch fresh spreadsheet data.
-    pub async fn fetch(google_api_key: &str) -> Result<Spreadsheet, reqwest::Error> {
+    pub async fn fetch(google_api_key: &str) -> Result<Spreadsheet, googlesheets::error::Error> {
         fetch_spreadsheet(google_api_key).await
     }
 
+    /// Read the spreadsheet exported as a workbook (.xlsx), without API key.
+    /// Columns are detected from the header row.
+    #[cfg(feature = "xlsx")]
+    pub fn from_xlsx(bytes: &[u8]) -> Result<Spreadsheet, xlsx::XlsxError> {
+        Self::from_xlsx_with_layout(bytes, SpreadsheetLayout::default(), ColumnsFrom::HeaderRow)
+    }
+
+    /// Read the sheet of the given layout from the exported workbook (.xlsx).
+    #[cfg(feature = "xlsx")]
+    pub fn from_xlsx_with_layout(
+        bytes: &[u8],
+        layout: SpreadsheetLayout,
+        columns_from: ColumnsFrom,
+    ) -> Result<Spreadsheet, xlsx::XlsxError> {
+        xlsx::read_spreadsheet(bytes, layout, columns_from)
+    }
+
     /// iterator over dumb records - initial preparation of data for each spreadsheet row.
     /// Zips each row of simple format with rich format [`Cell`] for sources column(s)
     /// to produce a [`Dumb`]
@@ -68,51 +108,121 @@ impl Spreadsheet {
             .values
             .iter()
             .zip(self.styled_columns.drops.cells())
+            .zip(self.styled_columns.drops_datamined.cells())
             .zip(self.styled_columns.drops_verify.cells())
             .enumerate()
             .map(
-                |(row_index, ((spreadsheet_row, drops_cell), drops_verify_cell))| {
+                |(
+                    row_index,
+                    (((spreadsheet_row, drops_cell), drops_datamined_cell), drops_verify_cell),
+                )| {
                     let cells = StyledDropsCells {
                         drops: drops_cell,
+                        drops_datamined: drops_datamined_cell,
                         drops_verify: drops_verify_cell,
                     };
 
-                    Dumb::create(row_index, spreadsheet_row, cells)
+                    Dumb::create(row_index, spreadsheet_row, cells, &self.layout)
                 },
             )
     }
 }
 
 mod fetch {
-    use crate::spreadsheet::{
-        StyledDropsColumns, DROPS_COLUMN_LETTER, DROPS_DATAMINED_COLUMN_LETTER,
-        DROPS_VERIFY_COLUMN_LETTER,
+    use super::{rich::RichColumn, Spreadsheet, SpreadsheetLayout, StyledDropsColumns};
+    use googlesheets::{
+        error::Error,
+        range::CellRef,
+        sheet::{Credential, ValueRange},
+        A1Range, Column, SheetsClient,
     };
 
-    use super::{rich::RichColumn, Spreadsheet};
-    use googlesheets::sheet::ValueRange;
+    /// Where to take columns of the [`SpreadsheetLayout`] from.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub enum ColumnsFrom {
+        /// Detect from the header row. Columns, that are not found, keep letters of the layout
+        /// and are listed in [`Spreadsheet::missing_columns`].
+        #[default]
+        HeaderRow,
+        /// Use letters of the layout as they are.
+        Layout,
+    }
 
     /// Fetch fresh spreadsheet data.
-    pub async fn fetch_spreadsheet(google_api_key: &str) -> Result<Spreadsheet, reqwest::Error> {
-        let sheet = fetch_table_sheet(google_api_key).await?;
+    pub async fn fetch_spreadsheet(google_api_key: &str) -> Result<Spreadsheet, Error> {
+        fetch_spreadsheet_with(SheetsClient::global(), google_api_key).await
+    }
+
+    /// Fetch fresh spreadsheet data with given client, for example one pointed to a fake server.
+    pub async fn fetch_spreadsheet_with(
+        client: &SheetsClient,
+        google_api_key: &str,
+    ) -> Result<Spreadsheet, Error> {
+        fetch_spreadsheet_with_layout(
+            client,
+            google_api_key,
+            SpreadsheetLayout::default(),
+            ColumnsFrom::HeaderRow,
+        )
+        .await
+    }
+
+    /// Fetch fresh spreadsheet data of the given layout.
+    pub async fn fetch_spreadsheet_with_layout(
+        client: &SheetsClient,
+        google_api_key: &str,
+        mut layout: SpreadsheetLayout,
+        columns_from: ColumnsFrom,
+    ) -> Result<Spreadsheet, Error> {
+        let credential = Credential::ApiKey(google_api_key.to_owned());
+        let missing_columns = match columns_from {
+            ColumnsFrom::HeaderRow => {
+                let header = fetch_header_row(client, &credential, &layout).await?;
+                layout.detect_columns(&header)
+            }
+            ColumnsFrom::Layout => vec![],
+        };
+
+        let sheet = fetch_table_sheet(client, &credential, &layout).await?;
         let number_of_rows = sheet.values.len();
-        let styled_columns = fetch_styled_columns(google_api_key, number_of_rows).await?;
+        let styled_columns =
+            fetch_styled_columns(client, &credential, &layout, number_of_rows).await?;
 
         Ok(Spreadsheet {
             sheet,
             styled_columns,
+            layout,
+            missing_columns: missing_columns.into_iter().map(str::to_owned).collect(),
         })
     }
 
+    async fn fetch_header_row(
+        client: &SheetsClient,
+        credential: &Credential,
+        layout: &SpreadsheetLayout,
+    ) -> Result<Vec<serde_json::Value>, Error> {
+        let row = CellRef::row(layout.header_row);
+        let range = A1Range::sheet(layout.sheet.as_str())
+            .with_start(row)
+            .with_end(row);
+        let header = client
+            .read(&layout.spreadsheet_id, &range, credential)
+            .await?;
+        Ok(header.values.into_iter().next().unwrap_or_default())
+    }
+
     async fn fetch_styled_columns(
-        google_api_key: &str,
+        client: &SheetsClient,
+        credential: &Credential,
+        layout: &SpreadsheetLayout,
         number_of_rows: usize,
-    ) -> Result<StyledDropsColumns, reqwest::Error> {
-        let drops_rich = fetch_rich_column(google_api_key, DROPS_COLUMN_LETTER).await?;
+    ) -> Result<StyledDropsColumns, Error> {
+        let columns = &layout.columns;
+        let drops_rich = fetch_rich_column(client, credential, layout, columns.drops).await?;
         let drops_datamined_rich =
-            fetch_rich_column(google_api_key, DROPS_DATAMINED_COLUMN_LETTER).await?;
+            fetch_rich_column(client, credential, layout, columns.drops_datamined).await?;
         let drops_verify_rich =
-            fetch_rich_column(google_api_key, DROPS_VERIFY_COLUMN_LETTER).await?;
+            fetch_rich_column(client, credential, layout, columns.drops_verify).await?;
 
         let drops = RichColumn::new(drops_rich.sheets, number_of_rows);
         let drops_datamined = RichColumn::new(drops_datamined_rich.sheets, number_of_rows);
@@ -125,26 +235,124 @@ mod fetch {
         })
     }
 
-    async fn fetch_table_sheet(google_api_key: &str) -> Result<ValueRange, reqwest::Error> {
-        let spreadsheet_id = "1Pf2KNuGguZLyf6eu_R0E503U0QNyfMZqaRETsN5g6kU";
-        let sheet = "Cards_and_Hypotheses";
-        let range = format!("{sheet}!A3:Z");
-
-        let url = format!(
-        "https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values/{range}?key={google_api_key}"
-    );
-        let value_range: ValueRange = reqwest::get(url).await?.error_for_status()?.json().await?;
-        Ok(value_range)
+    async fn fetch_table_sheet(
+        client: &SheetsClient,
+        credential: &Credential,
+        layout: &SpreadsheetLayout,
+    ) -> Result<ValueRange, Error> {
+        let last = layout.columns.last().max(Column::letter('Z'));
+        let range = A1Range::columns(
+            layout.sheet.as_str(),
+            Column::A,
+            last,
+            layout.first_record_row(),
+        );
+        client
+            .read(&layout.spreadsheet_id, &range, credential)
+            .await
     }
 
     async fn fetch_rich_column(
-        google_api_key: &str,
-        letter: char,
-    ) -> Result<RichColumn, reqwest::Error> {
-        let spreadsheet_id = "1Pf2KNuGguZLyf6eu_R0E503U0QNyfMZqaRETsN5g6kU";
-        let sheet = "Cards_and_Hypotheses";
-        let column = letter;
-        let url = format!("https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}?&ranges={sheet}!{column}3:{column}&includeGridData=true&key={google_api_key}");
-        reqwest::get(url).await?.error_for_status()?.json().await
+        client: &SheetsClient,
+        credential: &Credential,
+        layout: &SpreadsheetLayout,
+        column: Column,
+    ) -> Result<RichColumn, Error> {
+        let range = A1Range::column(layout.sheet.as_str(), column, layout.first_record_row());
+        client
+            .get_spreadsheet(&layout.spreadsheet_id, &[&range], true, credential)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googlesheets::fake::FakeSheets;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn fetches_from_fake_sheets() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.set_values(
+            SPREADSHEET_ID,
+            SHEET_TITLE,
+            vec![
+                vec![json!("Greynote"), json!("Card")],
+                vec![],
+                vec![
+                    json!(""),
+                    json!("The Doctor"),
+                    json!(""),
+                    json!("Done"),
+                    json!(""),
+                    json!("n/a"),
+                    json!("Burning Monkey"),
+                ],
+            ],
+        );
+
+        let spreadsheet = fetch_spreadsheet_with(&fake.client(), "key").await.unwrap();
+        assert_eq!(spreadsheet.sheet.values.len(), 1);
+        assert_eq!(spreadsheet.missing_columns.len(), 9);
+
+        let dumbs = spreadsheet
+            .dumb_records()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(dumbs[0].card, "The Doctor");
+        assert_eq!(dumbs[0].drops[0].name, "Burning Monkey");
+        assert!(dumbs[0].drops_datamined.is_empty());
+        assert!(dumbs[0].drops_to_verify.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetches_with_columns_from_header_row() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.set_values(
+            SPREADSHEET_ID,
+            SHEET_TITLE,
+            vec![
+                vec![],
+                vec![
+                    json!("Card"),
+                    json!("Greynote"),
+                    json!("Tag Hypothesis"),
+                    json!("Confidence"),
+                    json!("Remaining Work"),
+                    json!("Drops"),
+                    json!("Datamined"),
+                    json!("Verify"),
+                    json!("Notes"),
+                ],
+                vec![
+                    json!("The Doctor"),
+                    json!("n/a"),
+                    json!(""),
+                    json!("Done"),
+                    json!("n/a"),
+                    json!("Burning Monkey"),
+                    json!(""),
+                    json!(""),
+                    json!("Moved columns"),
+                ],
+            ],
+        );
+
+        let spreadsheet = fetch_spreadsheet_with(&fake.client(), "key").await.unwrap();
+        assert!(spreadsheet.missing_columns.is_empty());
+        assert_eq!(
+            spreadsheet.layout.columns.drops,
+            googlesheets::Column::letter('F')
+        );
+
+        let dumbs = spreadsheet
+            .dumb_records()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(dumbs[0].id, 3);
+        assert_eq!(dumbs[0].card, "The Doctor");
+        assert_eq!(dumbs[0].drops[0].name, "Burning Monkey");
+        assert_eq!(dumbs[0].notes.as_deref(), Some("Moved columns"));
     }
 }
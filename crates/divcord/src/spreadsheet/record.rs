@@ -3,7 +3,12 @@
 use std::fmt::Display;
 
 use super::rich::{DropsFrom, ParseCellError};
-use crate::{dropsource::Source, spreadsheet::StyledDropsCells};
+use crate::{
+    dropsource::Source,
+    parse::{resolve_source, DivcordColumn},
+    poe_data::PoeData,
+    spreadsheet::{ColumnMapping, SpreadsheetLayout, StyledDropsCells},
+};
 use divi::cards::CheckCardName;
 use serde::{Deserialize, Serialize};
 use serde_json::{Error as SerdeJsonError, Value};
@@ -25,6 +30,192 @@ pub struct Record {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
     pub verify_sources: Vec<Source>,
+    /// Sources of the datamined column, not verified by drops.
+    #[serde(default)]
+    pub datamined_sources: Vec<Source>,
+    /// Structured [`Record::tag_hypothesis`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hypothesis: Option<TagHypothesis>,
+}
+
+/// Researchers' hypothesis of [`Record::tag_hypothesis`], for example
+/// `(den_area OR cave_area) & rigwald_area` or `wealthy_area + Boss with `has_two_handed_melee``.
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TagHypothesis {
+    /// Sources, resolved the same way as drop sources.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<Source>,
+    /// Game tags, such as `desert_area` or `elder_guardian_boss`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Everything else, such as "boss only".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text: Vec<String>,
+}
+
+impl TagHypothesis {
+    pub fn parse(text: &str, dumb: &Dumb, poe_data: &PoeData) -> Self {
+        let mut hypothesis = TagHypothesis::default();
+        hypothesis.parse_segment(text, dumb, poe_data);
+        dedup(&mut hypothesis.sources);
+        dedup(&mut hypothesis.categories);
+        hypothesis
+    }
+
+    /// Resolves the whole segment first, so that names with brackets or `&` stay intact,
+    /// such as "Breach encounters (Keepers)" or "The Ossuary (A5)".
+    /// Otherwise splits it by top level operators or unwraps the brackets.
+    fn parse_segment(&mut self, segment: &str, dumb: &Dumb, poe_data: &PoeData) {
+        let segment = segment.trim();
+        if !segment.chars().any(char::is_alphanumeric) {
+            return;
+        }
+
+        if let Some(sources) = resolve_source(segment, dumb, poe_data) {
+            self.sources.extend(sources);
+            return;
+        }
+
+        let operands = split_top_level(segment);
+        if operands.len() > 1 {
+            for operand in operands {
+                self.parse_segment(operand, dumb, poe_data);
+            }
+            return;
+        }
+
+        if let Some(inner) = unwrap_brackets(segment) {
+            self.parse_segment(inner, dumb, poe_data);
+            return;
+        }
+
+        self.parse_operand(segment, dumb, poe_data);
+    }
+
+    fn parse_operand(&mut self, operand: &str, dumb: &Dumb, poe_data: &PoeData) {
+        let lowercased = operand.to_lowercase();
+        if let Some(tags) = ["tags:", "tag:"]
+            .iter()
+            .find_map(|prefix| lowercased.strip_prefix(prefix))
+        {
+            self.categories.extend(
+                tags.split([' ', ','])
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_owned),
+            );
+            return;
+        }
+
+        let mut words = vec![];
+        for word in operand.split_whitespace() {
+            let trimmed = word.trim_matches(['`', '"', '\'', ',', '.']);
+            match is_game_tag(trimmed) {
+                true => self.categories.push(trimmed.to_owned()),
+                false => words.push(word),
+            }
+        }
+        let rest = words.join(" ");
+        let rest = rest.trim_matches([' ', ',']);
+        if rest.is_empty() {
+            return;
+        }
+
+        if let Some(sources) = resolve_source(rest, dumb, poe_data) {
+            self.sources.extend(sources);
+            return;
+        }
+        let parts = rest
+            .split(',')
+            .map(|part| resolve_source(part.trim(), dumb, poe_data))
+            .collect::<Option<Vec<_>>>();
+        match parts {
+            Some(parts) if rest.contains(',') => self.sources.extend(parts.into_iter().flatten()),
+            _ => self.text.push(rest.to_owned()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty() && self.categories.is_empty() && self.text.is_empty()
+    }
+
+    /// Sources of the hypothesis, that are not among the confirmed sources of the record.
+    pub fn unconfirmed_sources<'a>(&'a self, record: &Record) -> Vec<&'a Source> {
+        self.sources
+            .iter()
+            .filter(|source| !record.sources.contains(source))
+            .collect()
+    }
+
+    /// Record has confirmed sources, but none of the hypothesis sources is among them.
+    pub fn contradicts(&self, record: &Record) -> bool {
+        !self.sources.is_empty()
+            && !record.sources.is_empty()
+            && self.unconfirmed_sources(record).len() == self.sources.len()
+    }
+}
+
+/// Splits by `;`, new lines, `&`, `+`, `OR` and `AND` (case-insensitive) outside of brackets.
+fn split_top_level(text: &str) -> Vec<&str> {
+    const WORD_OPERATORS: [&str; 2] = [" or ", " and "];
+
+    let mut operands = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ';' | '\n' | '&' | '+' if depth == 0 => {
+                operands.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            ' ' if depth == 0 => {
+                let Some(operator) = WORD_OPERATORS.iter().find(|operator| {
+                    text.get(i..i + operator.len())
+                        .is_some_and(|s| s.eq_ignore_ascii_case(operator))
+                }) else {
+                    continue;
+                };
+                operands.push(&text[start..i]);
+                start = i + operator.len();
+                // Skip the operator, the last space included.
+                chars.nth(operator.len() - 2);
+            }
+            _ => {}
+        }
+    }
+    operands.push(&text[start..]);
+    operands
+}
+
+/// Inner text of `(...)`, if the brackets wrap the whole text.
+fn unwrap_brackets(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('(')?.strip_suffix(')')?;
+    let mut depth = 0usize;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return None,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    Some(inner)
+}
+
+/// Removes all duplicates, keeping the first occurrence.
+fn dedup<T: Eq + std::hash::Hash + Clone>(items: &mut Vec<T>) {
+    let mut seen = std::collections::HashSet::new();
+    items.retain(|item| seen.insert(item.clone()));
+}
+
+/// Snake case identifier with underscores, such as `vaal_pyramid_area`.
+fn is_game_tag(word: &str) -> bool {
+    word.contains('_')
+        && !word.starts_with('_')
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 /// Represents spreadsheet's row after the initial preparation (but before the main parsing).
@@ -54,6 +245,10 @@ pub struct Dumb {
     /// G "non-atlas locations"
     pub drops: Vec<DropsFrom>,
 
+    /// H
+    #[serde(default)]
+    pub drops_datamined: Vec<DropsFrom>,
+
     /// I
     pub drops_to_verify: Vec<DropsFrom>,
 
@@ -68,8 +263,9 @@ pub enum ParseDumbErrKind {
     CardName(ParseCardNameError),
     Confidence(SerdeJsonError),
     RemainingWork(SerdeJsonError),
-    StyledCell(ParseCellError),
-    DuplicateDropSource(DropsFrom),
+    /// Drops cell of the column could not be split into sources.
+    StyledCell(DivcordColumn, ParseCellError),
+    DuplicateDropSource(DivcordColumn, DropsFrom),
 }
 
 impl Display for ParseDumbError {
@@ -105,12 +301,12 @@ impl Display for ParseDumbError {
                     "{preparsing_phase} {record_id} {card}. Parse remaining work error. {error}"
                 )
             }
-            ParseDumbErrKind::DuplicateDropSource(drops_from) => write!(
+            ParseDumbErrKind::DuplicateDropSource(_, drops_from) => write!(
                 f,
                 "{preparsing_phase} {record_id} {card}. Duplicate drop source: {}",
                 drops_from.name
             ),
-            ParseDumbErrKind::StyledCell(parse_cell_error) => write!(
+            ParseDumbErrKind::StyledCell(_, parse_cell_error) => write!(
                 f,
                 "{preparsing_phase} {record_id} {card} Could not parse styled cell into chunks. {parse_cell_error}"
             ),
@@ -133,21 +329,34 @@ impl ParseDumbError {
             kind,
         }
     }
-}
 
-impl Dumb {
-    pub fn record_id(row_index: usize) -> usize {
-        row_index + 3
+    /// Column of the cell, that could not be parsed.
+    pub fn column(&self) -> DivcordColumn {
+        match self.kind {
+            ParseDumbErrKind::Greynote(_) => DivcordColumn::GreyNote,
+            ParseDumbErrKind::CardName(_) => DivcordColumn::Card,
+            ParseDumbErrKind::Confidence(_) => DivcordColumn::Confidence,
+            ParseDumbErrKind::RemainingWork(_) => DivcordColumn::RemainingWork,
+            ParseDumbErrKind::StyledCell(column, _)
+            | ParseDumbErrKind::DuplicateDropSource(column, _) => column,
+        }
     }
+}
 
+impl Dumb {
+    /// Columns are taken from the layout, letters in comments are of the default layout.
     pub fn create(
         row_index: usize,
         spreadsheet_row: &[Value],
         cells: StyledDropsCells,
+        layout: &SpreadsheetLayout,
     ) -> Result<Self, ParseDumbError> {
-        let record_id = Dumb::record_id(row_index);
+        let record_id = layout.record_id(row_index);
+        let columns = &layout.columns;
+        let cell = |column| ColumnMapping::cell(spreadsheet_row, column);
+
         // B 1 Card name
-        let card = parse_card_name(&spreadsheet_row[1]).map_err(|parse_card_name_error| {
+        let card = parse_card_name(cell(columns.card)).map_err(|parse_card_name_error| {
             let card_name = match parse_card_name_error {
                 ParseCardNameError::CellValueIsNotStr(_) => {
                     "Invalid card name cell value".to_owned()
@@ -163,40 +372,42 @@ impl Dumb {
         })?;
 
         // A 0 Greynote
-        let greynote: GreyNote =
-            serde_json::from_value(spreadsheet_row[0].clone()).map_err(|parse_greynote_error| {
+        let greynote: GreyNote = serde_json::from_value(cell(columns.greynote).clone()).map_err(
+            |parse_greynote_error| {
                 ParseDumbError::new(
                     record_id,
                     card.clone(),
                     ParseDumbErrKind::Greynote(parse_greynote_error),
                 )
-            })?;
+            },
+        )?;
 
         // C 2 Tag hypothesis
-        let tag_hypothesis = parse_string_cell(&spreadsheet_row[2]);
+        let tag_hypothesis = parse_string_cell(cell(columns.tag_hypothesis));
 
         // D 3 3.25 Confidence
-        let confidence: Confidence = serde_json::from_value(spreadsheet_row[3].clone()).map_err(
-            |parse_confidence_error| {
+        let confidence: Confidence = serde_json::from_value(cell(columns.confidence).clone())
+            .map_err(|parse_confidence_error| {
                 ParseDumbError::new(
                     record_id,
                     card.clone(),
                     ParseDumbErrKind::Confidence(parse_confidence_error),
                 )
-            },
-        )?;
+            })?;
 
         // E 4 Old Confidence SKIP
 
         // F 5 Remaining work
-        let remaining_work: RemainingWork = serde_json::from_value(spreadsheet_row[5].clone())
-            .map_err(|parse_remaining_error| {
-                ParseDumbError::new(
-                    record_id,
-                    card.clone(),
-                    ParseDumbErrKind::RemainingWork(parse_remaining_error),
-                )
-            })?;
+        let remaining_work: RemainingWork = serde_json::from_value(
+            cell(columns.remaining_work).clone(),
+        )
+        .map_err(|parse_remaining_error| {
+            ParseDumbError::new(
+                record_id,
+                card.clone(),
+                ParseDumbErrKind::RemainingWork(parse_remaining_error),
+            )
+        })?;
 
         // G 6 - drops
         let drops = cells
@@ -206,7 +417,7 @@ impl Dumb {
                 ParseDumbError::new(
                     record_id,
                     card.clone(),
-                    ParseDumbErrKind::StyledCell(parse_styled_cell_error),
+                    ParseDumbErrKind::StyledCell(DivcordColumn::Sources, parse_styled_cell_error),
                 )
             })?;
 
@@ -217,11 +428,27 @@ impl Dumb {
                 return Err(ParseDumbError::new(
                     record_id,
                     card.clone(),
-                    ParseDumbErrKind::DuplicateDropSource(drop.clone()),
+                    ParseDumbErrKind::DuplicateDropSource(DivcordColumn::Sources, drop.clone()),
                 ));
             }
         }
 
+        // H 7 - Datamined
+        let drops_datamined =
+            cells
+                .drops_datamined
+                .drops_from()
+                .map_err(|parse_styled_cell_error| {
+                    ParseDumbError::new(
+                        record_id,
+                        card.clone(),
+                        ParseDumbErrKind::StyledCell(
+                            DivcordColumn::Datamined,
+                            parse_styled_cell_error,
+                        ),
+                    )
+                })?;
+
         // I 8 - To Confirm or Verify
         let drops_to_verify =
             cells
@@ -231,12 +458,15 @@ impl Dumb {
                     ParseDumbError::new(
                         record_id,
                         card.clone(),
-                        ParseDumbErrKind::StyledCell(parse_styled_cell_error),
+                        ParseDumbErrKind::StyledCell(
+                            DivcordColumn::Verify,
+                            parse_styled_cell_error,
+                        ),
                     )
                 })?;
 
         // J 9 - Notes
-        let notes = spreadsheet_row.get(9).and_then(parse_string_cell);
+        let notes = parse_string_cell(cell(columns.notes));
 
         Ok(Dumb {
             id: record_id,
@@ -246,6 +476,7 @@ impl Dumb {
             confidence,
             remaining_work,
             drops,
+            drops_datamined,
             drops_to_verify,
             notes,
         })
@@ -407,3 +638,71 @@ pub enum RemainingWork {
     #[serde(rename = "story")]
     Story,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dropsource::predefined::PredefinedSource,
+        poe_data::{
+            act::{ActArea, ActAreaId},
+            cards::{Card, CardsData},
+            maps::Map,
+        },
+    };
+
+    #[test]
+    fn parses_tag_hypothesis() {
+        let dumb = Dumb::test("Some Card");
+        let poe_data = PoeData {
+            acts: vec![ActArea::test("1_1_2", "The Coast", 1, 2)],
+            cards: CardsData::test([Card::test("Some Card", 0)]),
+            maps: vec![Map::test("Some Map")],
+            ..Default::default()
+        };
+
+        let hypothesis = TagHypothesis::parse(
+            "(desert_area OR `wealthy_area`) & Some Map; boss only\nKirac Missions, Delirium Currency Rewards",
+            &dumb,
+            &poe_data,
+        );
+        let map = Source::Map("Some Map".to_owned());
+        let kirac = Source::Predefined(PredefinedSource::KiracMissions);
+        assert_eq!(hypothesis.categories, ["desert_area", "wealthy_area"]);
+        assert_eq!(
+            hypothesis.sources,
+            [
+                map.clone(),
+                kirac.clone(),
+                Source::Predefined(PredefinedSource::DeliriumCurrencyRewards)
+            ]
+        );
+        assert_eq!(hypothesis.text, ["boss only"]);
+
+        let record = Record {
+            sources: vec![Source::Predefined(PredefinedSource::Delirium)],
+            ..Record::test("Some Card")
+        };
+        assert!(hypothesis.contradicts(&record));
+        assert!(!hypothesis.contradicts(&Record {
+            sources: vec![kirac],
+            ..record
+        }));
+
+        let hypothesis = TagHypothesis::parse(
+            "Breach encounters (Keepers) or (The Coast (A1) and desert_area) + Some Map, desert_area\nBreach encounters (Keepers)",
+            &dumb,
+            &poe_data,
+        );
+        assert_eq!(
+            hypothesis.sources,
+            [
+                Source::Predefined(PredefinedSource::BreachEncountersKeepers),
+                Source::Act(ActAreaId::new("1_1_2".to_owned())),
+                map,
+            ]
+        );
+        assert_eq!(hypothesis.categories, ["desert_area"]);
+        assert!(hypothesis.text.is_empty());
+    }
+}
@@ -25,6 +25,29 @@ impl RichColumn {
         Self { sheets }
     }
 
+    /// Column of one cell per row, built without the API (see [`super::xlsx`]).
+    #[cfg(feature = "xlsx")]
+    pub fn from_cells(title: &str, cells: Vec<Cell>) -> Self {
+        let row_data = cells
+            .into_iter()
+            .map(|cell| RowData { values: vec![cell] })
+            .collect();
+        Self {
+            sheets: vec![Sheet {
+                data: vec![Data {
+                    start_column: None,
+                    row_data,
+                }],
+                properties: Properties {
+                    sheet_id: 0,
+                    title: title.to_owned(),
+                    index: 0,
+                    sheet_type: "GRID".to_owned(),
+                },
+            }],
+        }
+    }
+
     pub fn cells(&self) -> impl Iterator<Item = &Cell> {
         let sheet = &self.sheets[0];
         let data = &sheet.data[0];
@@ -97,6 +120,22 @@ impl Display for ParseCellError {
 }
 
 impl Cell {
+    #[cfg(feature = "xlsx")]
+    pub fn new(
+        text_content: Option<String>,
+        text_format: TextFormat,
+        text_format_runs: Option<Vec<TextFormatRun>>,
+    ) -> Self {
+        Cell {
+            effective_format: EffectiveFormat {
+                background_color: ProtobufColor::rgb(255, 255, 255),
+                text_format,
+            },
+            text_content,
+            text_format_runs,
+        }
+    }
+
     pub fn drops_from(&self) -> Result<Vec<DropsFrom>, ParseCellError> {
         Ok(self
             .text_fragments()?
@@ -232,6 +271,16 @@ pub struct ProtobufColor {
 }
 
 impl ProtobufColor {
+    pub fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        // Middle of the component, so that `into_hexcolor` floors it back to the same value.
+        let fraction = |component: u8| Some((f32::from(component) + 0.5).min(255.0) / 255.0);
+        ProtobufColor {
+            red: fraction(red),
+            green: fraction(green),
+            blue: fraction(blue),
+        }
+    }
+
     pub fn into_hexcolor(self) -> HexColor {
         let red_frac = self.red.unwrap_or(0.0);
         let green_frac = self.green.unwrap_or(0.0);
@@ -302,6 +351,16 @@ pub struct FontStyles {
     pub strikethrough: bool,
 }
 
+impl TextFormat {
+    pub fn new(color: ProtobufColor, italic: bool, strikethrough: bool) -> Self {
+        TextFormat {
+            color,
+            italic,
+            strikethrough,
+        }
+    }
+}
+
 impl From<TextFormat> for FontStyles {
     fn from(value: TextFormat) -> Self {
         FontStyles {
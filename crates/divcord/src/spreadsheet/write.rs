@@ -0,0 +1,664 @@
+//! Reverse of the parsing: [`Record`] back into cells of the spreadsheet,
+//! for programmatic fixes such as bulk renames of maps after a patch.
+//!
+//! Sources are written the way the parser reads them: acts and act bosses are italic
+//! and go after the other sources of the cell. [`RecordsUpdate`] compares records,
+//! so cells of unchanged fields are not touched. A changed drops cell is rewritten only
+//! if it holds nothing but its sources: cells with comments, struck through or coloured
+//! sources or sources, that could not be parsed, are listed in [`RecordsUpdate::skipped`]
+//! to be edited by hand. Render it with [`RecordsUpdate::to_markdown`] for a dry run,
+//! then [`RecordsUpdate::write`] it.
+
+use super::{
+    record::{GreyNote, Record},
+    rich::{Cell, FontStyles, HexColor, Text},
+    ColumnMapping, Spreadsheet, SpreadsheetLayout,
+};
+use crate::{dropsource::Source, parse::DivcordColumn, poe_data::PoeData};
+use googlesheets::{
+    error::{Error, GoogleError},
+    sheet::{
+        CellData, CellFormat, ExtendedValue, GridCoordinate, RowData, SheetId, TextFormat,
+        TextFormatRun, UpdateCells,
+    },
+    CredentialProvider, SheetsClient,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::HashMap, fmt::Write};
+
+/// Cell of the record. Drops cells consist of normal text, followed by italic text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordCell {
+    pub column: DivcordColumn,
+    pub text: Vec<Text>,
+}
+
+impl RecordCell {
+    pub fn plain(column: DivcordColumn, content: &str) -> Self {
+        let text = match content.is_empty() {
+            true => vec![],
+            false => vec![Text {
+                content: content.to_owned(),
+                styles: FontStyles::default(),
+            }],
+        };
+        RecordCell { column, text }
+    }
+
+    /// Sources of the drops column, acts and act bosses italic.
+    pub fn sources(column: DivcordColumn, sources: &[Source], poe_data: &PoeData) -> Self {
+        let (italic, normal): (Vec<&Source>, Vec<&Source>) = sources
+            .iter()
+            .partition(|source| matches!(source, Source::Act(_) | Source::ActBoss(_)));
+        let normal = normal
+            .into_iter()
+            .map(|source| source.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let italic = act_names(&italic, poe_data).join("; ");
+
+        let text = |content: String, italic: bool| Text {
+            content,
+            styles: FontStyles {
+                italic,
+                ..Default::default()
+            },
+        };
+        let text = match (normal.is_empty(), italic.is_empty()) {
+            (true, true) => vec![],
+            (false, true) => vec![text(normal, false)],
+            (true, false) => vec![text(italic, true)],
+            (false, false) => vec![text(format!("{normal}; "), false), text(italic, true)],
+        };
+        RecordCell { column, text }
+    }
+
+    pub fn content(&self) -> String {
+        self.text.iter().map(|text| text.content.as_str()).collect()
+    }
+
+    /// Value with italic of the whole cell or text format runs, if the cell is mixed.
+    pub fn cell_data(&self) -> CellData {
+        let italic = |italic: bool| TextFormat {
+            italic: Some(italic),
+            strikethrough: Some(false),
+        };
+        let mut start_index = 0;
+        let text_format_runs = match self.text.len() {
+            0 | 1 => vec![],
+            _ => self
+                .text
+                .iter()
+                .map(|text| {
+                    let run = TextFormatRun {
+                        start_index,
+                        format: italic(text.styles.italic),
+                    };
+                    start_index += text.content.encode_utf16().count() as u32;
+                    run
+                })
+                .collect(),
+        };
+        let cell_italic = match self.text.as_slice() {
+            [text] => text.styles.italic,
+            _ => false,
+        };
+
+        CellData {
+            user_entered_value: Some(ExtendedValue {
+                string_value: self.content(),
+            }),
+            user_entered_format: Some(CellFormat {
+                text_format: italic(cell_italic),
+            }),
+            text_format_runs,
+        }
+    }
+}
+
+/// Act areas as `The Ossuary (A5/A10)`, act bosses by name.
+fn act_names(sources: &[&Source], poe_data: &PoeData) -> Vec<String> {
+    let mut names: Vec<(String, Vec<u8>)> = vec![];
+    for source in sources {
+        let (name, act) = match source {
+            Source::Act(id) => match poe_data.act_area_id(id) {
+                Some(area) => (area.name.clone(), Some(area.act)),
+                None => (id.as_str().to_owned(), None),
+            },
+            other => (other.to_string(), None),
+        };
+        match names
+            .iter_mut()
+            .find(|(existing, acts)| *existing == name && acts.len() == 1 && act.is_some())
+        {
+            Some((_, acts)) => acts.extend(act),
+            None => names.push((name, act.into_iter().collect())),
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|(name, acts)| match acts.is_empty() {
+            true => name,
+            false => {
+                let acts = acts.iter().map(|act| format!("A{act}")).collect::<Vec<_>>();
+                format!("{name} ({})", acts.join("/"))
+            }
+        })
+        .collect()
+}
+
+/// All cells of the record.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordRow {
+    /// Record id, the sheet row.
+    pub id: usize,
+    pub card: String,
+    pub cells: Vec<RecordCell>,
+}
+
+impl RecordRow {
+    pub fn new(record: &Record, poe_data: &PoeData) -> Self {
+        let cells = [
+            DivcordColumn::GreyNote,
+            DivcordColumn::Card,
+            DivcordColumn::TagHypothesis,
+            DivcordColumn::Confidence,
+            DivcordColumn::RemainingWork,
+            DivcordColumn::Sources,
+            DivcordColumn::Datamined,
+            DivcordColumn::Verify,
+            DivcordColumn::Notes,
+        ]
+        .into_iter()
+        .map(|column| record_cell(record, column, poe_data))
+        .collect();
+
+        RecordRow {
+            id: record.id,
+            card: record.card.clone(),
+            cells,
+        }
+    }
+
+    /// Values of the row as the values API returns them, from column A to the last record column.
+    pub fn values(&self, layout: &SpreadsheetLayout) -> Vec<Value> {
+        let mut values =
+            vec![Value::String(String::new()); layout.columns.last().index() as usize + 1];
+        for cell in &self.cells {
+            values[cell.column.column(&layout.columns).index() as usize] =
+                Value::String(cell.content());
+        }
+        values
+    }
+}
+
+fn record_cell(record: &Record, column: DivcordColumn, poe_data: &PoeData) -> RecordCell {
+    let optional =
+        |text: &Option<String>| RecordCell::plain(column, text.as_deref().unwrap_or_default());
+    match column {
+        DivcordColumn::GreyNote => match record.greynote {
+            GreyNote::Empty => RecordCell::plain(column, ""),
+            ref greynote => RecordCell::plain(column, &greynote.to_string()),
+        },
+        DivcordColumn::Card => RecordCell::plain(column, &record.card),
+        DivcordColumn::TagHypothesis => optional(&record.tag_hypothesis),
+        DivcordColumn::Confidence => RecordCell::plain(column, &record.confidence.to_string()),
+        DivcordColumn::RemainingWork => {
+            RecordCell::plain(column, &record.remaining_work.to_string())
+        }
+        DivcordColumn::Sources => RecordCell::sources(column, &record.sources, poe_data),
+        DivcordColumn::Datamined => {
+            RecordCell::sources(column, &record.datamined_sources, poe_data)
+        }
+        DivcordColumn::Verify => RecordCell::sources(column, &record.verify_sources, poe_data),
+        DivcordColumn::Notes => optional(&record.notes),
+    }
+}
+
+/// Text of the record cell in the sheet, `None` for rows past the end of the sheet.
+fn sheet_text(spreadsheet: &Spreadsheet, id: usize, column: DivcordColumn) -> Option<String> {
+    let row_index = id.checked_sub(spreadsheet.layout.first_record_row() as usize)?;
+    let row = spreadsheet.sheet.values.get(row_index)?;
+    Some(
+        match ColumnMapping::cell(row, column.column(&spreadsheet.layout.columns)) {
+            Value::String(text) => text.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        },
+    )
+}
+
+/// Styled cell of the record in the sheet, `None` for columns other than drops.
+fn sheet_drops_cell(spreadsheet: &Spreadsheet, id: usize, column: DivcordColumn) -> Option<&Cell> {
+    let row_index = id.checked_sub(spreadsheet.layout.first_record_row() as usize)?;
+    let columns = &spreadsheet.styled_columns;
+    let rich_column = match column {
+        DivcordColumn::Sources => &columns.drops,
+        DivcordColumn::Datamined => &columns.drops_datamined,
+        DivcordColumn::Verify => &columns.drops_verify,
+        _ => return None,
+    };
+    rich_column.cells().nth(row_index)
+}
+
+/// Whether the drops cell of the sheet holds more than the sources of the old record,
+/// so rewriting it would lose comments, struck through or coloured sources
+/// or sources, that could not be parsed.
+fn holds_more_than_sources(cell: &Cell, text: &str, rendered: &str) -> bool {
+    let items = |text: &str| {
+        let mut items = text
+            .split(';')
+            .map(str::trim)
+            .filter(|item| !item.is_empty() && *item != "n/a")
+            .map(|item| item.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>();
+        items.sort();
+        items
+    };
+    if items(text) != items(rendered) {
+        return true;
+    }
+
+    match cell.text_fragments() {
+        Ok(fragments) => fragments.iter().any(|fragment| {
+            fragment.styles.strikethrough || fragment.styles.color != HexColor::White
+        }),
+        Err(_) => true,
+    }
+}
+
+/// Whether the field of the column differs between records.
+fn changed(old: &Record, new: &Record, column: DivcordColumn) -> bool {
+    match column {
+        DivcordColumn::GreyNote => old.greynote != new.greynote,
+        DivcordColumn::Card => old.card != new.card,
+        DivcordColumn::TagHypothesis => old.tag_hypothesis != new.tag_hypothesis,
+        DivcordColumn::Confidence => old.confidence != new.confidence,
+        DivcordColumn::RemainingWork => old.remaining_work != new.remaining_work,
+        DivcordColumn::Sources => old.sources != new.sources,
+        DivcordColumn::Datamined => old.datamined_sources != new.datamined_sources,
+        DivcordColumn::Verify => old.verify_sources != new.verify_sources,
+        DivcordColumn::Notes => old.notes != new.notes,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CellUpdate {
+    /// Text of the cell in the sheet, `None` for rows past the end of the sheet.
+    pub from: Option<String>,
+    pub to: RecordCell,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowUpdate {
+    pub id: usize,
+    pub card: String,
+    pub cells: Vec<CellUpdate>,
+}
+
+/// Changed drops cell, that is not written, because the sheet holds more than its sources.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedCell {
+    pub id: usize,
+    pub card: String,
+    /// Text of the cell in the sheet.
+    pub from: String,
+    pub to: RecordCell,
+}
+
+/// Cells to write, so the sheet holds the new records.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordsUpdate {
+    pub rows: Vec<RowUpdate>,
+    /// Changed cells, that have to be edited by hand.
+    pub skipped: Vec<SkippedCell>,
+}
+
+impl RecordsUpdate {
+    /// Records are matched by id (sheet row). Only changed fields are written,
+    /// rows of new ids are written whole. Rows of removed records are not touched.
+    /// `old` are the records parsed from the `spreadsheet`, its cells are compared
+    /// with the records before they are overwritten.
+    pub fn new(
+        spreadsheet: &Spreadsheet,
+        old: &[Record],
+        new: &[Record],
+        poe_data: &PoeData,
+    ) -> Self {
+        let old_by_id: HashMap<usize, &Record> = old.iter().map(|r| (r.id, r)).collect();
+        let mut update = RecordsUpdate::default();
+        for record in new {
+            let row = RecordRow::new(record, poe_data);
+            let old_row = old_by_id
+                .get(&record.id)
+                .map(|old_record| (*old_record, RecordRow::new(old_record, poe_data)));
+
+            let mut cells = vec![];
+            for (index, to) in row.cells.into_iter().enumerate() {
+                let rendered = match &old_row {
+                    Some((old_record, _)) if !changed(old_record, record, to.column) => continue,
+                    Some((_, old_row)) => old_row.cells[index].content(),
+                    None => String::new(),
+                };
+
+                let from = sheet_text(spreadsheet, record.id, to.column);
+                let drops_cell = sheet_drops_cell(spreadsheet, record.id, to.column);
+                match (&from, drops_cell) {
+                    (Some(text), Some(cell)) if holds_more_than_sources(cell, text, &rendered) => {
+                        update.skipped.push(SkippedCell {
+                            id: record.id,
+                            card: record.card.clone(),
+                            from: text.clone(),
+                            to,
+                        })
+                    }
+                    _ => cells.push(CellUpdate { from, to }),
+                }
+            }
+
+            if !cells.is_empty() {
+                update.rows.push(RowUpdate {
+                    id: record.id,
+                    card: record.card.clone(),
+                    cells,
+                });
+            }
+        }
+
+        update
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// `updateCells` requests, one per cell.
+    pub fn requests(&self, layout: &SpreadsheetLayout, sheet_id: &SheetId) -> Vec<UpdateCells> {
+        self.rows
+            .iter()
+            .flat_map(|row| {
+                row.cells.iter().map(|cell| UpdateCells {
+                    start: GridCoordinate {
+                        sheet_id: sheet_id.clone(),
+                        row_index: row.id.saturating_sub(1) as u32,
+                        column_index: cell.to.column.column(&layout.columns).index(),
+                    },
+                    rows: vec![RowData {
+                        values: vec![cell.to.cell_data()],
+                    }],
+                    fields: String::from(
+                        "userEnteredValue,userEnteredFormat.textFormat.italic,textFormatRuns",
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the cells into the sheet of the layout. Needs a credential with write access.
+    pub async fn write(
+        &self,
+        client: &SheetsClient,
+        layout: &SpreadsheetLayout,
+        credential: impl CredentialProvider,
+    ) -> Result<(), Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let sheet_id = client
+            .sheet_id_by_title(&layout.spreadsheet_id, &layout.sheet, &credential)
+            .await?
+            .ok_or_else(|| {
+                Error::GoogleError(GoogleError {
+                    code: 404,
+                    message: format!("Sheet {} not found", layout.sheet),
+                    status: String::from("NOT_FOUND"),
+                })
+            })?;
+        client
+            .update_cells(
+                &layout.spreadsheet_id,
+                self.requests(layout, &sheet_id),
+                &credential,
+            )
+            .await
+    }
+
+    /// Dry run: what would be written and what has to be edited by hand.
+    pub fn to_markdown(&self, layout: &SpreadsheetLayout) -> String {
+        let mut md = String::new();
+        if self.is_empty() {
+            md.push_str("No changes.\n");
+        }
+
+        for row in &self.rows {
+            writeln!(md, "### {} (row {})\n", row.card, row.id).unwrap();
+            for cell in &row.cells {
+                let column = cell.to.column.column(&layout.columns);
+                let to = one_line(&cell.to.content());
+                match &cell.from {
+                    Some(from) => {
+                        writeln!(md, "- {column}: `{}` → `{to}`", one_line(from)).unwrap()
+                    }
+                    None => writeln!(md, "- {column}: `{to}`").unwrap(),
+                }
+            }
+            md.push('\n');
+        }
+
+        if !self.skipped.is_empty() {
+            md.push_str("### Not written, edit by hand\n\n");
+            for cell in &self.skipped {
+                let column = cell.to.column.column(&layout.columns);
+                writeln!(
+                    md,
+                    "- {} {column}{}: `{}` → `{}`",
+                    cell.card,
+                    cell.id,
+                    one_line(&cell.from),
+                    one_line(&cell.to.content())
+                )
+                .unwrap();
+            }
+        }
+        md
+    }
+}
+
+fn one_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse::records_with_collect_all_errors,
+        poe_data::{
+            act::{ActArea, ActAreaId},
+            cards::{Card, CardsData},
+            maps::Map,
+        },
+        spreadsheet::{fetch_spreadsheet_with, SHEET_TITLE, SPREADSHEET_ID},
+    };
+    use googlesheets::fake::{FakeCell, FakeSheets};
+    use serde_json::json;
+
+    fn poe_data() -> PoeData {
+        PoeData {
+            acts: vec![
+                ActArea::test("1_5_5", "The Ossuary", 5, 50),
+                ActArea::test("2_10_5", "The Ossuary", 10, 100),
+            ],
+            cards: CardsData::test([Card::test("The Doctor", 0)]),
+            maps: vec![Map::test("Dunes Map"), Map::test("Strand Map")],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_record_row() {
+        let act = |id: &str| Source::Act(ActAreaId::new(id.to_owned()));
+        let record = Record {
+            sources: vec![
+                act("1_5_5"),
+                Source::Map("Dunes Map".to_owned()),
+                act("2_10_5"),
+            ],
+            verify_sources: vec![Source::ActBoss("Brutus, Lord Incarcerator".to_owned())],
+            ..Record::test("The Doctor")
+        };
+
+        let row = RecordRow::new(&record, &poe_data());
+        let values = row.values(&SpreadsheetLayout::default());
+        assert_eq!(values[1], "The Doctor");
+        assert_eq!(values[3], "Done");
+        assert_eq!(values[5], "n/a");
+        assert_eq!(values[6], "Dunes Map; The Ossuary (A5/A10)");
+        assert_eq!(values[8], "Brutus, Lord Incarcerator");
+
+        let drops = row.cells[5].cell_data();
+        assert_eq!(drops.text_format_runs.len(), 2);
+        assert_eq!(drops.text_format_runs[1].start_index, 11);
+        assert_eq!(drops.text_format_runs[1].format.italic, Some(true));
+        let verify = row.cells[7].cell_data();
+        assert!(verify.text_format_runs.is_empty());
+        assert_eq!(
+            verify.user_entered_format.unwrap().text_format.italic,
+            Some(true)
+        );
+    }
+
+    /// White font cell of the fake sheet, maps are expected in white font.
+    fn white_cell(value: &str) -> FakeCell {
+        FakeCell {
+            value: json!(value),
+            effective_format: Some(json!({
+                "backgroundColor": {},
+                "textFormat": {
+                    "foregroundColor": {"red": 1, "green": 1, "blue": 1},
+                    "italic": false,
+                    "strikethrough": false
+                }
+            })),
+            text_format_runs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_changed_cells() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.set_values(
+            SPREADSHEET_ID,
+            SHEET_TITLE,
+            vec![
+                vec![json!("Greynote"), json!("Card")],
+                vec![],
+                vec![
+                    json!(""),
+                    json!("The Doctor"),
+                    json!(""),
+                    json!("Done"),
+                    json!(""),
+                    json!("n/a"),
+                    json!("Dunes Map [boss only]"),
+                    json!("Dunes Map"),
+                    json!(""),
+                    json!("Keep this note"),
+                ],
+            ],
+        );
+        fake.set_cell(
+            SPREADSHEET_ID,
+            SHEET_TITLE,
+            2,
+            6,
+            white_cell("Dunes Map [boss only]"),
+        );
+        fake.set_cell(SPREADSHEET_ID, SHEET_TITLE, 2, 7, white_cell("Dunes Map"));
+        let client = fake.client();
+        let poe_data = poe_data();
+        let layout = SpreadsheetLayout::default();
+        let spreadsheet = fetch_spreadsheet_with(&client, "key").await.unwrap();
+        let old = records_with_collect_all_errors(&spreadsheet, &poe_data).unwrap();
+
+        let mut new = old.clone();
+        new[0].sources = vec![
+            Source::Map("Strand Map".to_owned()),
+            Source::Act(ActAreaId::new("1_5_5".to_owned())),
+        ];
+        new[0].datamined_sources = vec![Source::Map("Strand Map".to_owned())];
+        let update = RecordsUpdate::new(&spreadsheet, &old, &new, &poe_data);
+        assert_eq!(update.rows.len(), 1);
+        assert_eq!(update.rows[0].cells.len(), 1);
+        assert_eq!(update.skipped.len(), 1);
+        let md = update.to_markdown(&layout);
+        assert!(md.contains("- H: `Dunes Map` → `Strand Map`"));
+        assert!(md
+            .contains("- The Doctor G3: `Dunes Map [boss only]` → `Strand Map; The Ossuary (A5)`"));
+        assert!(RecordsUpdate::new(&spreadsheet, &old, &old, &poe_data).is_empty());
+
+        update
+            .write(
+                &client,
+                &layout,
+                googlesheets::sheet::Credential::ApiKey("key".to_owned()),
+            )
+            .await
+            .unwrap();
+        let values = fake.values(SPREADSHEET_ID, SHEET_TITLE).unwrap();
+        assert_eq!(values[2][6], "Dunes Map [boss only]");
+        assert_eq!(values[2][7], "Strand Map");
+        assert_eq!(values[2][9], "Keep this note");
+
+        let spreadsheet = fetch_spreadsheet_with(&client, "key").await.unwrap();
+        let written = records_with_collect_all_errors(&spreadsheet, &poe_data).unwrap();
+        assert_eq!(written[0].datamined_sources, new[0].datamined_sources);
+        assert_eq!(written[0].sources, old[0].sources);
+    }
+
+    #[tokio::test]
+    async fn skips_struck_through_sources() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.set_values(
+            SPREADSHEET_ID,
+            SHEET_TITLE,
+            vec![
+                vec![json!("Greynote"), json!("Card")],
+                vec![],
+                vec![
+                    json!(""),
+                    json!("The Doctor"),
+                    json!(""),
+                    json!("Done"),
+                    json!(""),
+                    json!("n/a"),
+                    json!("Dunes Map"),
+                    json!("Strand Map"),
+                ],
+            ],
+        );
+        fake.set_cell(SPREADSHEET_ID, SHEET_TITLE, 2, 6, white_cell("Dunes Map"));
+        let mut struck_through = white_cell("Strand Map");
+        struck_through.effective_format.as_mut().unwrap()["textFormat"]["strikethrough"] =
+            json!(true);
+        fake.set_cell(SPREADSHEET_ID, SHEET_TITLE, 2, 7, struck_through);
+        let poe_data = poe_data();
+        let spreadsheet = fetch_spreadsheet_with(&fake.client(), "key").await.unwrap();
+        let old = records_with_collect_all_errors(&spreadsheet, &poe_data).unwrap();
+
+        let mut new = old.clone();
+        new[0].datamined_sources = vec![Source::Map("Strand Map".to_owned())];
+        let update = RecordsUpdate::new(&spreadsheet, &old, &new, &poe_data);
+        assert!(update.is_empty());
+        assert_eq!(update.skipped[0].from, "Strand Map");
+    }
+}
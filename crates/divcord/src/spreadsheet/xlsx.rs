@@ -0,0 +1,730 @@
+#![cfg(feature = "xlsx")]
+//! Offline import of the spreadsheet exported as a workbook (File → Download → .xlsx).
+//!
+//! Produces the same [`ValueRange`] and [`StyledDropsColumns`] as the Google Sheets API,
+//! so the parse pipeline runs without an API key. Only what the parser needs is read:
+//! cell values and italic, strikethrough and RGB colour of cell fonts and rich text runs.
+//! Theme and indexed colours are treated as the default font colour.
+//! OpenDocument exports (.ods) are not supported and fail with [`XlsxError::OpenDocument`].
+
+use super::{
+    rich::{Cell, Format, ProtobufColor, RichColumn, TextFormat, TextFormatRun},
+    ColumnsFrom, Spreadsheet, SpreadsheetLayout, StyledDropsColumns,
+};
+use googlesheets::{
+    sheet::{Dimension, ValueRange},
+    Column,
+};
+use quick_xml::{
+    events::{attributes::AttrError, BytesStart, Event},
+    Reader,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::{Cursor, Read},
+};
+use zip::{result::ZipError, ZipArchive};
+
+/// Limit of a decompressed workbook part, so that a zip bomb does not exhaust the memory.
+pub const MAX_PART_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum XlsxError {
+    /// Not a zip archive or unsupported zip features.
+    Zip(ZipError),
+    Xml(quick_xml::Error),
+    Io(std::io::Error),
+    /// Required file of the workbook is missing, for example `xl/workbook.xml`.
+    MissingPart(String),
+    NotUtf8(String),
+    /// Part of the workbook is larger than [`MAX_PART_SIZE`] when decompressed.
+    PartTooLarge(String),
+    /// Spreadsheet exported as OpenDocument (.ods), download it as .xlsx instead.
+    OpenDocument,
+    SheetNotFound(String),
+}
+
+impl Display for XlsxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XlsxError::Zip(err) => write!(f, "Invalid workbook archive: {err}"),
+            XlsxError::Xml(err) => write!(f, "Invalid workbook xml: {err}"),
+            XlsxError::Io(err) => err.fmt(f),
+            XlsxError::MissingPart(part) => write!(f, "Workbook has no {part}"),
+            XlsxError::NotUtf8(part) => write!(f, "{part} is not valid UTF-8"),
+            XlsxError::PartTooLarge(part) => {
+                write!(f, "{part} is larger than {MAX_PART_SIZE} bytes")
+            }
+            XlsxError::OpenDocument => {
+                f.write_str("OpenDocument spreadsheets (.ods) are not supported, use .xlsx")
+            }
+            XlsxError::SheetNotFound(title) => write!(f, "Workbook has no sheet {title}"),
+        }
+    }
+}
+
+impl std::error::Error for XlsxError {}
+
+impl From<std::io::Error> for XlsxError {
+    fn from(value: std::io::Error) -> Self {
+        XlsxError::Io(value)
+    }
+}
+
+impl From<ZipError> for XlsxError {
+    fn from(value: ZipError) -> Self {
+        XlsxError::Zip(value)
+    }
+}
+
+impl From<quick_xml::Error> for XlsxError {
+    fn from(value: quick_xml::Error) -> Self {
+        XlsxError::Xml(value)
+    }
+}
+
+impl From<AttrError> for XlsxError {
+    fn from(value: AttrError) -> Self {
+        XlsxError::Xml(value.into())
+    }
+}
+
+/// Reads the sheet of the layout from the workbook bytes.
+pub fn read_spreadsheet(
+    bytes: &[u8],
+    mut layout: SpreadsheetLayout,
+    columns_from: ColumnsFrom,
+) -> Result<Spreadsheet, XlsxError> {
+    let mut archive = Archive::new(bytes)?;
+    if !archive.has("xl/workbook.xml") && archive.has("content.xml") {
+        return Err(XlsxError::OpenDocument);
+    }
+    let sheet_path = sheet_path(
+        &archive.text("xl/workbook.xml")?,
+        &archive.text("xl/_rels/workbook.xml.rels")?,
+        &layout.sheet,
+    )?;
+    let shared_strings = match archive.has("xl/sharedStrings.xml") {
+        true => parse_shared_strings(&archive.text("xl/sharedStrings.xml")?)?,
+        false => vec![],
+    };
+    let cell_fonts = match archive.has("xl/styles.xml") {
+        true => parse_cell_fonts(&archive.text("xl/styles.xml")?)?,
+        false => vec![],
+    };
+    let rows = parse_worksheet(&archive.text(&sheet_path)?, &shared_strings)?;
+    let missing_columns = match columns_from {
+        ColumnsFrom::HeaderRow => {
+            let header = row_values(rows.get(&layout.header_row), u32::MAX);
+            layout.detect_columns(&header)
+        }
+        ColumnsFrom::Layout => vec![],
+    };
+
+    let last_column = layout.columns.last().max(Column::letter('Z'));
+    let first_row = layout.first_record_row();
+    let last_row = rows
+        .range(first_row..)
+        .rev()
+        .find(|(_, cells)| {
+            cells
+                .range(..=last_column.index())
+                .any(|(_, cell)| !cell.text.is_empty())
+        })
+        .map(|(row, _)| *row);
+    let record_rows = last_row.map(|last| first_row..=last).into_iter().flatten();
+
+    let values = record_rows
+        .clone()
+        .map(|row| row_values(rows.get(&row), last_column.index()))
+        .collect();
+    let rich_column = |column: Column| {
+        let cells = record_rows
+            .clone()
+            .map(|row| {
+                rows.get(&row)
+                    .and_then(|cells| cells.get(&column.index()))
+                    .map(|cell| cell.to_rich_cell(&cell_fonts))
+                    .unwrap_or_else(|| Cell::new(None, Font::default().text_format(), None))
+            })
+            .collect();
+        RichColumn::from_cells(&layout.sheet, cells)
+    };
+    let styled_columns = StyledDropsColumns {
+        drops: rich_column(layout.columns.drops),
+        drops_datamined: rich_column(layout.columns.drops_datamined),
+        drops_verify: rich_column(layout.columns.drops_verify),
+    };
+
+    let range = format!(
+        "{}!A{first_row}:{}{}",
+        layout.sheet,
+        last_column.letters(),
+        last_row.unwrap_or(first_row)
+    );
+    Ok(Spreadsheet {
+        sheet: ValueRange::new(Dimension::Rows, range, values),
+        styled_columns,
+        layout,
+        missing_columns: missing_columns.into_iter().map(str::to_owned).collect(),
+    })
+}
+
+/// Values of the row up to the last non-empty cell, like the API returns them.
+fn row_values(cells: Option<&BTreeMap<u32, SheetCell>>, last_column: u32) -> Vec<Value> {
+    let Some(cells) = cells else {
+        return vec![];
+    };
+    let cells = cells
+        .range(..=last_column)
+        .filter(|(_, cell)| !cell.text.is_empty())
+        .collect::<Vec<_>>();
+    let Some((len, _)) = cells.last() else {
+        return vec![];
+    };
+
+    let mut values = vec![Value::String(String::new()); **len as usize + 1];
+    for (column, cell) in cells {
+        values[*column as usize] = Value::String(cell.text.to_string());
+    }
+    values
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Font {
+    italic: bool,
+    strikethrough: bool,
+    rgb: Option<(u8, u8, u8)>,
+}
+
+impl Font {
+    fn text_format(&self) -> TextFormat {
+        let (red, green, blue) = self.rgb.unwrap_or((255, 255, 255));
+        TextFormat::new(
+            ProtobufColor::rgb(red, green, blue),
+            self.italic,
+            self.strikethrough,
+        )
+    }
+}
+
+/// Text of the cell. Runs without their own font use the font of the cell.
+#[derive(Debug, Clone, Default)]
+struct RichText {
+    runs: Vec<(String, Option<Font>)>,
+}
+
+impl RichText {
+    fn plain(text: String) -> Self {
+        RichText {
+            runs: vec![(text, None)],
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.runs.iter().all(|(text, _)| text.is_empty())
+    }
+}
+
+impl Display for RichText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.runs.iter().try_for_each(|(text, _)| f.write_str(text))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SheetCell {
+    text: RichText,
+    /// Index of the cell format in `styles.xml`.
+    style: usize,
+}
+
+impl SheetCell {
+    /// Runs of the same italic and strikethrough are merged, as the API returns them.
+    /// Start indexes are byte offsets, which is how [`Cell::text_fragments`] slices the text.
+    fn to_rich_cell(&self, cell_fonts: &[Font]) -> Cell {
+        let cell_font = cell_fonts.get(self.style).cloned().unwrap_or_default();
+        let mut text = String::new();
+        let mut runs: Vec<(usize, Font)> = vec![];
+        for (content, font) in &self.text.runs {
+            if content.is_empty() {
+                continue;
+            }
+            let font = font.clone().unwrap_or_else(|| cell_font.clone());
+            match runs.last() {
+                Some((_, last))
+                    if last.italic == font.italic && last.strikethrough == font.strikethrough => {}
+                _ => runs.push((text.len(), font)),
+            }
+            text.push_str(content);
+        }
+
+        let text_content = (!text.is_empty()).then_some(text);
+        let rgb = cell_font
+            .rgb
+            .or_else(|| runs.first().and_then(|(_, font)| font.rgb));
+        if runs.len() < 2 {
+            let font = runs.pop().map(|(_, font)| font).unwrap_or(cell_font);
+            return Cell::new(text_content, Font { rgb, ..font }.text_format(), None);
+        }
+
+        let text_format_runs = runs
+            .into_iter()
+            .map(|(start, font)| TextFormatRun {
+                start_index: (start > 0).then_some(start),
+                format: Some(Format {
+                    italic: Some(font.italic),
+                    strikethrough: Some(font.strikethrough),
+                }),
+                font_family: None,
+            })
+            .collect();
+        Cell::new(
+            text_content,
+            Font { rgb, ..cell_font }.text_format(),
+            Some(text_format_runs),
+        )
+    }
+}
+
+/// Path of the sheet xml inside of the archive.
+fn sheet_path(workbook: &str, rels: &str, title: &str) -> Result<String, XlsxError> {
+    let not_found = || XlsxError::SheetNotFound(title.to_owned());
+    let relationship_id =
+        find_attr(workbook, b"sheet", (b"name", title), b"id")?.ok_or_else(not_found)?;
+    let target = find_attr(rels, b"Relationship", (b"Id", &relationship_id), b"Target")?
+        .ok_or_else(not_found)?;
+
+    Ok(match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_owned(),
+        None => format!("xl/{target}"),
+    })
+}
+
+/// Attribute of the first `tag`, that has the `key` attribute with the `value`.
+fn find_attr(
+    xml: &str,
+    tag: &[u8],
+    (key, value): (&[u8], &str),
+    name: &[u8],
+) -> Result<Option<String>, XlsxError> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) | Event::Empty(start)
+                if start.local_name().as_ref() == tag
+                    && attr(&start, key)?.as_deref() == Some(value) =>
+            {
+                return attr(&start, name);
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Attribute by its local name, without namespace prefix.
+fn attr(tag: &BytesStart, name: &[u8]) -> Result<Option<String>, XlsxError> {
+    for attribute in tag.attributes() {
+        let attribute = attribute?;
+        if attribute.key.local_name().as_ref() == name {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn parse_shared_strings(xml: &str) -> Result<Vec<RichText>, XlsxError> {
+    let mut reader = Reader::from_str(xml);
+    let mut strings = vec![];
+    loop {
+        match reader.read_event()? {
+            Event::Empty(tag) if tag.local_name().as_ref() == b"si" => {
+                strings.push(RichText::default())
+            }
+            Event::Start(tag) if tag.local_name().as_ref() == b"si" => {
+                strings.push(read_rich_text(&mut reader, b"si")?)
+            }
+            Event::Eof => return Ok(strings),
+            _ => {}
+        }
+    }
+}
+
+/// Fonts of the cell formats (`cellXfs`), by the index of the format.
+fn parse_cell_fonts(xml: &str) -> Result<Vec<Font>, XlsxError> {
+    let mut reader = Reader::from_str(xml);
+    let mut fonts = vec![];
+    let mut font_ids = vec![];
+    let (mut in_fonts, mut in_cell_xfs) = (false, false);
+    loop {
+        let (tag, empty) = match reader.read_event()? {
+            Event::Start(tag) => (tag, false),
+            Event::Empty(tag) => (tag, true),
+            Event::End(tag) => {
+                match tag.local_name().as_ref() {
+                    b"fonts" => in_fonts = false,
+                    b"cellXfs" => in_cell_xfs = false,
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        match tag.local_name().as_ref() {
+            b"fonts" => in_fonts = !empty,
+            b"cellXfs" => in_cell_xfs = !empty,
+            b"font" if in_fonts => fonts.push(match empty {
+                true => Font::default(),
+                false => read_font(&mut reader, b"font")?,
+            }),
+            b"xf" if in_cell_xfs => font_ids.push(
+                attr(&tag, b"fontId")?
+                    .and_then(|id| id.parse().ok())
+                    .unwrap_or(0),
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(font_ids
+        .into_iter()
+        .map(|id: usize| fonts.get(id).cloned().unwrap_or_default())
+        .collect())
+}
+
+/// Cells by rows and columns (both 1-based rows and 0-based columns, like [`Column`]).
+fn parse_worksheet(
+    xml: &str,
+    shared_strings: &[RichText],
+) -> Result<BTreeMap<u32, BTreeMap<u32, SheetCell>>, XlsxError> {
+    let mut reader = Reader::from_str(xml);
+    let mut rows: BTreeMap<u32, BTreeMap<u32, SheetCell>> = BTreeMap::new();
+    let (mut row, mut next_column) = (0, 0);
+    loop {
+        let (tag, empty) = match reader.read_event()? {
+            Event::Start(tag) => (tag, false),
+            Event::Empty(tag) => (tag, true),
+            Event::Eof => break,
+            _ => continue,
+        };
+        match tag.local_name().as_ref() {
+            b"row" => {
+                row = attr(&tag, b"r")?
+                    .and_then(|r| r.parse().ok())
+                    .unwrap_or(row + 1);
+                next_column = 0;
+            }
+            b"c" => {
+                let (column, cell_row) = attr(&tag, b"r")?
+                    .and_then(|r| parse_cell_ref(&r))
+                    .unwrap_or((next_column, row));
+                next_column = column + 1;
+                if empty {
+                    continue;
+                }
+
+                let style = attr(&tag, b"s")?.and_then(|s| s.parse().ok()).unwrap_or(0);
+                let kind = attr(&tag, b"t")?;
+                let text = read_cell_text(&mut reader, kind.as_deref(), shared_strings)?;
+                rows.entry(cell_row)
+                    .or_default()
+                    .insert(column, SheetCell { text, style });
+            }
+            _ => {}
+        }
+    }
+    Ok(rows)
+}
+
+/// `B12` into 0-based column and 1-based row.
+fn parse_cell_ref(cell_ref: &str) -> Option<(u32, u32)> {
+    let digits = cell_ref.find(|c: char| c.is_ascii_digit())?;
+    let column = Column::from_letters(&cell_ref[..digits])?;
+    Some((column.index(), cell_ref[digits..].parse().ok()?))
+}
+
+fn read_cell_text(
+    reader: &mut Reader<&[u8]>,
+    kind: Option<&str>,
+    shared_strings: &[RichText],
+) -> Result<RichText, XlsxError> {
+    let mut value = String::new();
+    let mut inline = RichText::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) => match tag.local_name().as_ref() {
+                b"v" => value = read_text(reader, b"v")?,
+                b"is" => inline = read_rich_text(reader, b"is")?,
+                _ => {
+                    reader.read_to_end(tag.name())?;
+                }
+            },
+            Event::End(tag) if tag.local_name().as_ref() == b"c" => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(match kind {
+        Some("s") => value
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| shared_strings.get(index))
+            .cloned()
+            .unwrap_or_default(),
+        Some("inlineStr") => inline,
+        Some("b") => RichText::plain(if value == "1" { "TRUE" } else { "FALSE" }.to_owned()),
+        _ => RichText::plain(value),
+    })
+}
+
+/// Runs of `<si>` or `<is>`. Phonetic runs are skipped.
+fn read_rich_text(reader: &mut Reader<&[u8]>, end: &[u8]) -> Result<RichText, XlsxError> {
+    let mut text = RichText::default();
+    let mut font = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) => match tag.local_name().as_ref() {
+                b"r" => {}
+                b"rPr" => font = Some(read_font(reader, b"rPr")?),
+                b"t" => text.runs.push((read_text(reader, b"t")?, font.clone())),
+                _ => {
+                    reader.read_to_end(tag.name())?;
+                }
+            },
+            Event::End(tag) if tag.local_name().as_ref() == b"r" => font = None,
+            Event::End(tag) if tag.local_name().as_ref() == end => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+/// Children of `<font>` or `<rPr>`.
+fn read_font(reader: &mut Reader<&[u8]>, end: &[u8]) -> Result<Font, XlsxError> {
+    let mut font = Font::default();
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) | Event::Empty(tag) => {
+                let on = attr(&tag, b"val")?.is_none_or(|val| val != "0" && val != "false");
+                match tag.local_name().as_ref() {
+                    b"i" => font.italic = on,
+                    b"strike" => font.strikethrough = on,
+                    b"color" => font.rgb = attr(&tag, b"rgb")?.and_then(|argb| parse_rgb(&argb)),
+                    _ => {}
+                }
+            }
+            Event::End(tag) if tag.local_name().as_ref() == end => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(font)
+}
+
+/// `FF666666` (ARGB) or `666666`.
+fn parse_rgb(hex: &str) -> Option<(u8, u8, u8)> {
+    let rgb = hex.get(hex.len().checked_sub(6)?..)?;
+    let component = |i: usize| u8::from_str_radix(rgb.get(i..i + 2)?, 16).ok();
+    Some((component(0)?, component(2)?, component(4)?))
+}
+
+/// Text content until the end of the element, nested elements included.
+fn read_text(reader: &mut Reader<&[u8]>, end: &[u8]) -> Result<String, XlsxError> {
+    let mut text = String::new();
+    loop {
+        match reader.read_event()? {
+            Event::Text(content) => text.push_str(&content.unescape()?),
+            Event::CData(content) => text.push_str(&String::from_utf8_lossy(&content)),
+            Event::End(tag) if tag.local_name().as_ref() == end => break,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+struct Archive<'a> {
+    zip: ZipArchive<Cursor<&'a [u8]>>,
+    max_part_size: u64,
+}
+
+impl<'a> Archive<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self, XlsxError> {
+        Ok(Archive {
+            zip: ZipArchive::new(Cursor::new(bytes))?,
+            max_part_size: MAX_PART_SIZE,
+        })
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.zip.index_for_name(name).is_some()
+    }
+
+    /// Decompressed part, the declared size is not trusted.
+    fn text(&mut self, name: &str) -> Result<String, XlsxError> {
+        let too_large = || XlsxError::PartTooLarge(name.to_owned());
+        let file = match self.zip.by_name(name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Err(XlsxError::MissingPart(name.to_owned())),
+            Err(err) => return Err(err.into()),
+        };
+        if file.size() > self.max_part_size {
+            return Err(too_large());
+        }
+
+        let mut bytes = vec![];
+        file.take(self.max_part_size + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > self.max_part_size {
+            return Err(too_large());
+        }
+        String::from_utf8(bytes).map_err(|_| XlsxError::NotUtf8(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spreadsheet::rich::HexColor;
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, content) in files {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_values_and_styles() {
+        let workbook = r#"<?xml version="1.0" encoding="UTF-8"?>
+<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<sheets><sheet name="Other" sheetId="1" r:id="rId1"/><sheet name="Cards_and_Hypotheses" sheetId="2" r:id="rId2"/></sheets>
+</workbook>"#;
+        let rels = r#"<Relationships>
+<Relationship Id="rId1" Target="worksheets/sheet1.xml"/>
+<Relationship Id="rId2" Target="/xl/worksheets/sheet2.xml"/>
+</Relationships>"#;
+        let styles = r#"<styleSheet>
+<fonts count="2"><font><sz val="10"/></font><font><i val="0"/><color rgb="FF666666"/></font></fonts>
+<cellStyleXfs><xf fontId="1"/></cellStyleXfs>
+<cellXfs count="2"><xf fontId="0"/><xf fontId="1" applyFont="1"/></cellXfs>
+</styleSheet>"#;
+        let shared_strings = r#"<sst>
+<si><t>Card</t></si>
+<si><r><t xml:space="preserve">Dunes Map; </t></r><r><rPr><i/></rPr><t>The Ledge (A1)</t></r></si>
+<si><t>Hidden &amp; &quot;grey&quot;</t><rPh><t>ignored</t></rPh></si>
+</sst>"#;
+        let sheet = r#"<worksheet><sheetData>
+<row r="2">
+<c r="A2" t="inlineStr"><is><t>Greynote</t></is></c><c r="B2" t="s"><v>0</v></c>
+<c r="C2" t="inlineStr"><is><t>Tag Hypothesis</t></is></c><c r="D2" t="inlineStr"><is><t>Confidence</t></is></c>
+<c r="F2" t="inlineStr"><is><t>Remaining Work</t></is></c><c r="G2" t="inlineStr"><is><t>Drops</t></is></c>
+<c r="H2" t="inlineStr"><is><t>Datamined</t></is></c><c r="I2" t="inlineStr"><is><t>Verify</t></is></c>
+<c r="J2" t="inlineStr"><is><t>Notes</t></is></c>
+</row>
+<row r="3">
+<c r="B3" t="inlineStr"><is><t>The Doctor</t></is></c><c r="D3" t="str"><f>"Done"</f><v>Done</v></c>
+<c r="F3" t="inlineStr"><is><t>n/a</t></is></c><c r="G3" t="s"><v>1</v></c><c r="I3" s="1" t="s"><v>2</v></c>
+</row>
+<row r="5"><c r="A5" s="1"/></row>
+</sheetData></worksheet>"#;
+        let bytes = zip(&[
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            ("xl/styles.xml", styles),
+            ("xl/sharedStrings.xml", shared_strings),
+            ("xl/worksheets/sheet1.xml", "<worksheet/>"),
+            ("xl/worksheets/sheet2.xml", sheet),
+        ]);
+
+        let spreadsheet = Spreadsheet::from_xlsx(&bytes).unwrap();
+        assert_eq!(spreadsheet.sheet.values.len(), 1);
+        assert_eq!(spreadsheet.sheet.values[0][1], "The Doctor");
+        assert_eq!(spreadsheet.sheet.values[0][3], "Done");
+        assert_eq!(spreadsheet.sheet.values[0].len(), 9);
+
+        let dumbs = spreadsheet
+            .dumb_records()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(dumbs[0].id, 3);
+        assert_eq!(dumbs[0].card, "The Doctor");
+
+        let drops = &dumbs[0].drops;
+        assert_eq!(drops.len(), 2);
+        assert_eq!(drops[0].name, "Dunes Map");
+        assert!(!drops[0].styles.italic);
+        assert_eq!(drops[1].name, "The Ledge (A1)");
+        assert!(drops[1].styles.italic);
+
+        let verify = &dumbs[0].drops_to_verify;
+        assert_eq!(verify[0].name, r#"Hidden & "grey""#);
+        assert_eq!(verify[0].styles.color, HexColor::Grey);
+        assert_eq!(drops[0].styles.color, HexColor::White);
+    }
+
+    #[test]
+    fn reports_missing_sheet() {
+        let bytes = zip(&[
+            ("xl/workbook.xml", "<workbook><sheets/></workbook>"),
+            ("xl/_rels/workbook.xml.rels", "<Relationships/>"),
+        ]);
+        assert!(matches!(
+            Spreadsheet::from_xlsx(&bytes),
+            Err(XlsxError::SheetNotFound(_))
+        ));
+        assert!(matches!(
+            Spreadsheet::from_xlsx(b"not a zip"),
+            Err(XlsxError::Zip(_))
+        ));
+    }
+
+    #[test]
+    fn finds_sheet_with_non_ascii_name() {
+        let workbook = r#"<workbook><sheets><sheet name='Ёлка' r:id="rId7"/></sheets></workbook>"#;
+        let rels = r#"<Relationships><Relationship Id="rId7" Target="worksheets/sheet7.xml"/></Relationships>"#;
+        assert_eq!(
+            sheet_path(workbook, rels, "Ёлка").unwrap(),
+            "xl/worksheets/sheet7.xml"
+        );
+    }
+
+    #[test]
+    fn rejects_too_large_parts() {
+        let bytes = zip(&[("xl/workbook.xml", &"<workbook/>".repeat(100))]);
+        let mut archive = Archive::new(&bytes).unwrap();
+        archive.max_part_size = 100;
+        assert!(matches!(
+            archive.text("xl/workbook.xml"),
+            Err(XlsxError::PartTooLarge(_))
+        ));
+        assert!(matches!(
+            archive.text("xl/styles.xml"),
+            Err(XlsxError::MissingPart(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_open_document() {
+        let bytes = zip(&[
+            ("mimetype", "application/vnd.oasis.opendocument.spreadsheet"),
+            ("content.xml", "<office:document-content/>"),
+        ]);
+        assert!(matches!(
+            Spreadsheet::from_xlsx(&bytes),
+            Err(XlsxError::OpenDocument)
+        ));
+    }
+}
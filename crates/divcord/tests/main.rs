@@ -1,5 +1,5 @@
 use divcord::{
-    parse::parse_one_drops_from,
+    parse::{parse_one_drops_from, SourcesKind},
     spreadsheet::{
         record::{Confidence, Dumb, GreyNote, RemainingWork},
         rich::DropsFrom,
@@ -37,6 +37,7 @@ fn create_dumb(card: &str, drops_from: DropsFrom) -> Dumb {
         confidence: Confidence::Done,
         remaining_work: RemainingWork::NotApplicable,
         drops: vec![drops_from],
+        drops_datamined: vec![],
         drops_to_verify: vec![],
         notes: None,
     }
@@ -49,7 +50,7 @@ pub fn parse_drop(
 ) -> Result<Vec<divcord::Source>, divcord::parse::ParseDropsFromError> {
     let clone = drops_from.clone();
     let dumb = create_dumb(card, drops_from);
-    parse_one_drops_from(&clone, &dumb, poe_data)
+    parse_one_drops_from(&clone, &dumb, poe_data, SourcesKind::Source)
 }
 
 #[tokio::test]
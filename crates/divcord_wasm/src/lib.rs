@@ -1,6 +1,6 @@
 mod utils;
 
-use divcord::{ParseRecordError, PoeData, Record};
+use divcord::{ParseRecordError, PoeData, Record, Source};
 use utils::set_panic_hook;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
@@ -15,6 +15,7 @@ pub async fn fetch_divcord_records(
 
     let poe_data: PoeData =
         serde_wasm_bindgen::from_value(poe_data).map_err(|err| JsValue::from(err.to_string()))?;
+    poe_data.register_extra_sources();
     let spreadsheet = divcord::spreadsheet::fetch_spreadsheet(api_key)
         .await
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
@@ -51,10 +52,46 @@ extern "C" {
     fn log(s: &str);
 }
 
+/// Lookups of cards by source and sources by card. Build it once and reuse for many lookups,
+/// free functions below build it on every call.
+#[wasm_bindgen]
+pub struct DivcordIndex(divcord::DivcordIndex);
+
+#[wasm_bindgen]
+impl DivcordIndex {
+    #[wasm_bindgen(constructor)]
+    pub fn new(records: JsValue, poe_data: JsValue) -> Result<DivcordIndex, JsValue> {
+        set_panic_hook();
+        let poe_data: PoeData = serde_wasm_bindgen::from_value(poe_data)?;
+        poe_data.register_extra_sources();
+        let records: Vec<Record> = serde_wasm_bindgen::from_value(records)?;
+        let index = divcord::DivcordIndex::new(&records, &poe_data);
+        Ok(DivcordIndex(index))
+    }
+
+    pub fn cards_by_source(&self, source: JsValue) -> Result<JsValue, JsValue> {
+        let source: Source = serde_wasm_bindgen::from_value(source)?;
+        let cards = self.0.cards_by_source(&source);
+        Ok(serde_wasm_bindgen::to_value(&cards)?)
+    }
+
+    pub fn cards_by_source_types(&self, types: JsValue) -> Result<JsValue, JsValue> {
+        let types: Vec<String> = serde_wasm_bindgen::from_value(types)?;
+        let cards = self.0.cards_by_source_types(&types);
+        Ok(serde_wasm_bindgen::to_value(&cards)?)
+    }
+
+    pub fn sources_by_card(&self, card: String) -> Result<JsValue, JsValue> {
+        let sources = self.0.sources_by_card(&card).collect::<Vec<_>>();
+        Ok(serde_wasm_bindgen::to_value(&sources)?)
+    }
+}
+
 #[wasm_bindgen]
 pub fn find_cards_by_source_types(types: JsValue, records: JsValue, poe_data: JsValue) -> JsValue {
     set_panic_hook();
     let poe_data: PoeData = serde_wasm_bindgen::from_value(poe_data).unwrap();
+    poe_data.register_extra_sources();
 
     let types: Vec<String> = serde_wasm_bindgen::from_value(types).unwrap();
     let records: Vec<Record> = serde_wasm_bindgen::from_value(records).unwrap();
@@ -73,6 +110,7 @@ pub fn find_cards_by_source_types_strings(
     set_panic_hook();
     let types: Vec<String> = serde_json::from_str(&types).unwrap();
     let poe_data: PoeData = serde_json::from_str(&poe_data).unwrap();
+    poe_data.register_extra_sources();
     let records: Vec<Record> = serde_json::from_str(&records).unwrap();
 
     let cards = divcord::cards::cards_by_source_types(&types, &records, &poe_data);
@@ -81,6 +119,18 @@ pub fn find_cards_by_source_types_strings(
     serde_json::to_string(&cards).unwrap()
 }
 
+#[wasm_bindgen]
+pub fn find_sources_by_card(card: String, records: JsValue, poe_data: JsValue) -> JsValue {
+    set_panic_hook();
+    let poe_data: PoeData = serde_wasm_bindgen::from_value(poe_data).unwrap();
+    poe_data.register_extra_sources();
+    let records: Vec<Record> = serde_wasm_bindgen::from_value(records).unwrap();
+
+    let sources = divcord::cards::sources_by_card(&card, &records, &poe_data);
+
+    serde_wasm_bindgen::to_value(&sources).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn slug(s: String) -> String {
     slug::slugify(s)
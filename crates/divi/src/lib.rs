@@ -69,7 +69,10 @@ pub use crate::{
     consts::{CARDS, CONDENSING_FACTOR, LEGACY_CARDS},
     error::Error,
     prices::{DivinationCardPrice, Prices},
-    sample::{Column, Input, NameAmount, Order, Sample, TablePreferences},
+    sample::{
+        Column, Input, NameAmount, Order, Sample, SheetColumn, SheetColumns, SheetInput, SyncMode,
+        TablePreferences,
+    },
 };
 pub use poe::league::{League, TradeLeague};
 
@@ -6,10 +6,10 @@ use crate::{
     prices::Prices,
 };
 use csv::{ReaderBuilder, Trim};
-use googlesheets::sheet::ReadBatchResponse;
+use googlesheets::sheet::{ReadBatchResponse, ValueRange};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{fmt::Display, iter::zip};
+use std::fmt::Display;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -185,6 +185,128 @@ impl Sample {
         values
     }
 
+    /// Builds sheet values for updating an existing table instead of writing a new one.
+    ///
+    /// Rows are matched by card name. Columns of the existing table that are not sample columns
+    /// (user-added notes, formulas, etc.) keep their values for every matched card.
+    /// Sample columns missing from the existing header are appended to the right.
+    #[must_use]
+    pub fn sync_serde_values(
+        self,
+        existing: &[Vec<Value>],
+        preferences: Option<TablePreferences>,
+        mode: SyncMode,
+    ) -> Vec<Vec<Value>> {
+        let mut fresh = self.into_serde_values(preferences);
+        if fresh.is_empty() {
+            return fresh;
+        }
+        let fresh_rows = fresh.split_off(1);
+        let fresh_header = fresh.remove(0);
+
+        let Some(existing_header) = existing.first() else {
+            let mut values = vec![fresh_header];
+            values.extend(fresh_rows);
+            return values;
+        };
+
+        let mut header = existing_header.clone();
+        for column in &fresh_header {
+            if !header.iter().any(|h| same_header(h, column)) {
+                header.push(column.clone());
+            }
+        }
+        let width = header.len();
+
+        // position of each fresh column in the resulting header
+        let positions: Vec<usize> = fresh_header
+            .iter()
+            .map(|column| {
+                header
+                    .iter()
+                    .position(|h| same_header(h, column))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let fresh_name_index = fresh_header
+            .iter()
+            .position(|column| same_header(column, &json!(Column::Name)));
+
+        let Some(fresh_name_index) = fresh_name_index else {
+            // Without names rows can not be matched, so table is fully rewritten.
+            let mut values = vec![header];
+            values.extend(fresh_rows.into_iter().map(|row| {
+                let mut full = vec![Value::Null; width];
+                for (position, value) in positions.iter().zip(row) {
+                    full[*position] = value;
+                }
+                full
+            }));
+            return values;
+        };
+        let name_position = positions[fresh_name_index];
+
+        let row_name = |row: &[Value]| -> Option<String> {
+            row.get(name_position)
+                .and_then(Value::as_str)
+                .map(|name| name.trim().to_owned())
+        };
+
+        let mut existing_rows: Vec<Vec<Value>> = existing
+            .iter()
+            .skip(1)
+            .map(|row| {
+                let mut row = row.clone();
+                row.resize(width, Value::Null);
+                row
+            })
+            .collect();
+
+        let mut appended: Vec<Vec<Value>> = vec![];
+        let mut matched: Vec<bool> = vec![false; existing_rows.len()];
+        for fresh_row in fresh_rows {
+            let name = fresh_row[fresh_name_index].as_str().map(str::to_owned);
+            let index = name.as_deref().and_then(|name| {
+                existing_rows
+                    .iter()
+                    .position(|row| row_name(row).as_deref() == Some(name))
+            });
+
+            let fill = |row: &mut Vec<Value>| {
+                for (position, value) in positions.iter().zip(fresh_row) {
+                    row[*position] = value;
+                }
+            };
+            if let Some(index) = index {
+                matched[index] = true;
+                fill(&mut existing_rows[index]);
+            } else {
+                let mut row = vec![Value::Null; width];
+                fill(&mut row);
+                appended.push(row);
+            }
+        }
+
+        let mut values = vec![header];
+        match mode {
+            SyncMode::Merge => {
+                values.extend(existing_rows);
+                values.extend(appended);
+            }
+            SyncMode::Replace => {
+                let mut kept: Vec<Vec<Value>> = existing_rows
+                    .into_iter()
+                    .zip(matched)
+                    .filter_map(|(row, matched)| matched.then_some(row))
+                    .collect();
+                kept.extend(appended);
+                values.extend(kept);
+            }
+        }
+
+        values
+    }
+
     pub fn into_csv(self, preferences: Option<TablePreferences>) -> Result<String, CsvError> {
         let values = self.into_serde_values(preferences);
         let mut writer = csv::Writer::from_writer(vec![]);
@@ -348,6 +470,24 @@ pub enum Input {
     Sample(Sample),
 }
 
+/// How sample rows are written into an already existing table.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncMode {
+    /// Update rows of cards from the sample, keep all other rows.
+    #[default]
+    Merge,
+    /// Leave only cards from the sample. Extra columns of kept rows are preserved.
+    Replace,
+}
+
+fn same_header(a: &Value, b: &Value) -> bool {
+    match (a.as_str(), b.as_str()) {
+        (Some(a), Some(b)) => a.trim().eq_ignore_ascii_case(b.trim()),
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum Column {
@@ -371,22 +511,243 @@ impl Display for Column {
     }
 }
 
+/// Column of a sheet table, found by header text or by zero-based index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SheetColumn {
+    Index(usize),
+    Header(String),
+}
+
+/// Columns of sheet values, that hold card names and amounts.
+/// Columns left as `None` are found by the same headers as in CSV: name, amount, stackSize, Quantity.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetColumns {
+    pub name: Option<SheetColumn>,
+    pub amount: Option<SheetColumn>,
+}
+
+impl SheetColumns {
+    #[must_use]
+    pub const fn by_index(name: usize, amount: usize) -> SheetColumns {
+        SheetColumns {
+            name: Some(SheetColumn::Index(name)),
+            amount: Some(SheetColumn::Index(amount)),
+        }
+    }
+
+    /// Finds the header row and column indexes. Without any header columns, there is no header row.
+    fn locate(
+        &self,
+        rows: &[Vec<Value>],
+    ) -> Result<(Option<usize>, usize, usize), MissingHeadersError> {
+        if let (Some(SheetColumn::Index(name)), Some(SheetColumn::Index(amount))) =
+            (&self.name, &self.amount)
+        {
+            return Ok((None, *name, *amount));
+        }
+
+        let find = |row: &[Value], column: &Option<SheetColumn>, variants: &[&str]| match column {
+            Some(SheetColumn::Index(index)) => Some(*index),
+            Some(SheetColumn::Header(header)) => row
+                .iter()
+                .position(|cell| same_header(cell, &json!(header))),
+            None => row.iter().position(|cell| {
+                variants
+                    .iter()
+                    .any(|variant| same_header(cell, &json!(variant)))
+            }),
+        };
+
+        rows.iter()
+            .enumerate()
+            .find_map(|(index, row)| {
+                let name = find(row, &self.name, &["name"])?;
+                let amount = find(row, &self.amount, &["amount", "stackSize", "Quantity"])?;
+                Some((Some(index), name, amount))
+            })
+            .ok_or(MissingHeadersError)
+    }
+}
+
+/// Why sheet row did not make it into the sample.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    MissingName,
+    InvalidAmount,
+}
+
+/// Sheet row, that could not be read as name and amount.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRow {
+    /// 1-based row number in the sheet.
+    pub row: usize,
+    pub values: Vec<Value>,
+    pub reason: SkipReason,
+}
+
+/// Name-amount pairs read from sheet values.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetInput {
+    pub pairs: Vec<NameAmount>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+impl SheetInput {
+    /// Reads name and amount columns from one or several value ranges.
+    /// Several ranges are put side by side, so names and amounts can come from separate column ranges.
+    /// Amounts may be numbers or strings like `"1,024"`. Empty rows are ignored, other unusable rows are reported in [`SheetInput::skipped`].
+    ///
+    /// Without headers, the table of exactly two columns is read as name, amount.
+    ///
+    /// # Errors
+    /// [`MissingHeadersError`] if header columns can not be found.
+    pub fn from_value_ranges(
+        value_ranges: &[ValueRange],
+        columns: &SheetColumns,
+    ) -> Result<SheetInput, MissingHeadersError> {
+        let rows = join_value_ranges(value_ranges);
+        let first_row = value_ranges
+            .first()
+            .map_or(1, |value_range| first_row_number(&value_range.range));
+
+        let (header_row, name_column, amount_column) = match columns.locate(&rows) {
+            Ok(located) => located,
+            Err(_) if *columns == SheetColumns::default() && table_width(value_ranges) == 2 => {
+                (None, 0, 1)
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut input = SheetInput::default();
+        let data_start = header_row.map_or(0, |header_row| header_row + 1);
+        for (index, row) in rows.into_iter().enumerate().skip(data_start) {
+            if row.iter().all(is_blank) {
+                continue;
+            }
+
+            let name = row
+                .get(name_column)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|name| !name.is_empty());
+            let amount = row.get(amount_column).and_then(parse_amount);
+            match (name, amount) {
+                (Some(name), Some(amount)) => {
+                    input.pairs.push(NameAmount::new(name.to_owned(), amount));
+                }
+                (None, _) => input.skipped.push(SkippedRow {
+                    row: first_row + index,
+                    values: row,
+                    reason: SkipReason::MissingName,
+                }),
+                (Some(_), None) => input.skipped.push(SkippedRow {
+                    row: first_row + index,
+                    values: row,
+                    reason: SkipReason::InvalidAmount,
+                }),
+            }
+        }
+
+        Ok(input)
+    }
+}
+
+impl From<SheetInput> for Input {
+    fn from(input: SheetInput) -> Self {
+        Input::NameAmountPairs(input.pairs)
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+fn parse_amount(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(number) => match number.as_u64() {
+            Some(amount) => u32::try_from(amount).ok(),
+            // Whole floats like 3.0 are displayed without fraction
+            None => number
+                .as_f64()
+                .filter(|amount| amount.fract() == 0.0)
+                .and_then(|amount| amount.to_string().parse::<u32>().ok()),
+        },
+        Value::String(s) => s.trim().replace(',', "").parse::<u32>().ok(),
+        _ => None,
+    }
+}
+
+/// Row number, where range starts: `3` for `Sheet!F3:F`.
+fn first_row_number(range: &str) -> usize {
+    let cells = range.rsplit_once('!').map_or(range, |(_, cells)| cells);
+    cells
+        .trim_start_matches(|ch: char| ch.is_ascii_alphabetic())
+        .split(':')
+        .next()
+        .and_then(|digits| digits.parse().ok())
+        .unwrap_or(1)
+}
+
+fn table_width(value_ranges: &[ValueRange]) -> usize {
+    value_ranges.iter().map(range_width).sum()
+}
+
+fn range_width(value_range: &ValueRange) -> usize {
+    value_range
+        .values
+        .iter()
+        .map(Vec::len)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Puts rows of value ranges side by side. Short rows are padded, so columns of next range keep their places.
+fn join_value_ranges(value_ranges: &[ValueRange]) -> Vec<Vec<Value>> {
+    let height = value_ranges
+        .iter()
+        .map(|value_range| value_range.values.len())
+        .max()
+        .unwrap_or_default();
+
+    (0..height)
+        .map(|index| {
+            let mut row = vec![];
+            for value_range in value_ranges {
+                let width = range_width(value_range);
+                let mut part = value_range.values.get(index).cloned().unwrap_or_default();
+                part.resize(width, Value::String(String::new()));
+                row.extend(part);
+            }
+            row
+        })
+        .collect()
+}
+
 impl TryFrom<ReadBatchResponse> for Input {
     type Error = crate::error::Error;
 
     fn try_from(response: ReadBatchResponse) -> Result<Self, Self::Error> {
-        let names = response.value_ranges[0].values.clone();
-        let amounts = response.value_ranges[1].values.clone();
-
-        let v = zip(names, amounts)
-            .map(|(name, amount)| {
-                let name: String = serde_json::from_str(&name[0].to_string())?;
-                let amount: u32 =
-                    serde_json::from_str::<String>(&amount[0].to_string())?.parse::<u32>()?;
-                Ok(NameAmount { name, amount })
-            })
-            .collect::<Result<Vec<NameAmount>, Error>>()?;
-        Ok(Input::NameAmountPairs(v))
+        let input =
+            SheetInput::from_value_ranges(&response.value_ranges, &SheetColumns::default())?;
+        Ok(input.into())
+    }
+}
+
+impl TryFrom<ValueRange> for Input {
+    type Error = crate::error::Error;
+
+    fn try_from(value_range: ValueRange) -> Result<Self, Self::Error> {
+        let input = SheetInput::from_value_ranges(&[value_range], &SheetColumns::default())?;
+        Ok(input.into())
     }
 }
 
@@ -467,6 +828,43 @@ Encroaching Darkness,5\r\nThe Endless Darkness,1\r\nThe Endurance,19\r\nThe Enfo
         assert_eq!(trimmed.lines().next().unwrap(), "name,stackSize");
     }
 
+    #[test]
+    fn sync_serde_values_keeps_user_columns() {
+        let existing = vec![
+            vec![json!("name"), json!("amount"), json!("notes")],
+            vec![json!("The Doctor"), json!(1), json!("first one!")],
+            vec![json!("The Fool"), json!(3), json!("")],
+        ];
+        let sample = Sample::create(
+            Input::Csv(String::from("name,amount\rRain of Chaos,10\rThe Doctor,2")),
+            None,
+        )
+        .unwrap();
+        let preferences = TablePreferences {
+            cards_must_have_amount: true,
+            ..Default::default()
+        };
+
+        let merged =
+            sample
+                .clone()
+                .sync_serde_values(&existing, Some(preferences.clone()), SyncMode::Merge);
+        assert_eq!(merged.len(), 4);
+        assert_eq!(
+            merged[1],
+            vec![json!("The Doctor"), json!(2), json!("first one!")]
+        );
+        assert_eq!(merged[2], vec![json!("The Fool"), json!(3), json!("")]);
+        assert_eq!(
+            merged[3],
+            vec![json!("Rain of Chaos"), json!(10), Value::Null]
+        );
+
+        let replaced = sample.sync_serde_values(&existing, Some(preferences), SyncMode::Replace);
+        assert_eq!(replaced.len(), 3);
+        assert!(replaced.iter().all(|row| row[0] != json!("The Fool")));
+    }
+
     #[test]
     fn merge() {
         use std::fs::read_to_string;
@@ -488,4 +886,76 @@ Encroaching Darkness,5\r\nThe Endless Darkness,1\r\nThe Endurance,19\r\nThe Enfo
 
         assert_eq!(rain_of_chaos.amount, 1779);
     }
+
+    #[test]
+    fn sheet_input_finds_columns_by_header() {
+        let value_range = ValueRange::new(
+            googlesheets::sheet::Dimension::Rows,
+            String::from("My tab!A1:D"),
+            vec![
+                vec![json!("Stacks of 3.26")],
+                vec![json!("#"), json!("Card"), json!("notes"), json!("Count")],
+                vec![json!(1), json!("Rain of Chaos"), json!(""), json!(30)],
+                vec![json!(2), json!("The Doctor"), json!(""), json!("1,024")],
+                vec![],
+                vec![json!(3), json!(""), json!(""), json!(4)],
+                vec![json!(4), json!("The Fiend"), json!(""), json!("lots")],
+            ],
+        );
+        let columns = SheetColumns {
+            name: Some(SheetColumn::Header(String::from("card"))),
+            amount: Some(SheetColumn::Header(String::from("count"))),
+        };
+
+        let input = SheetInput::from_value_ranges(&[value_range], &columns).unwrap();
+        let pairs: Vec<(&str, u32)> = input
+            .pairs
+            .iter()
+            .map(|pair| (pair.name.as_str(), pair.amount))
+            .collect();
+        assert_eq!(pairs, vec![("Rain of Chaos", 30), ("The Doctor", 1024)]);
+
+        let skipped: Vec<(usize, SkipReason)> = input
+            .skipped
+            .into_iter()
+            .map(|row| (row.row, row.reason))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![(6, SkipReason::MissingName), (7, SkipReason::InvalidAmount)]
+        );
+    }
+
+    #[test]
+    fn sheet_input_from_separate_column_ranges() {
+        let names = ValueRange::new(
+            googlesheets::sheet::Dimension::Rows,
+            String::from("3.28!F3:F"),
+            vec![vec![json!("Rain of Chaos")], vec![json!("The Doctor")]],
+        );
+        let amounts = ValueRange::new(
+            googlesheets::sheet::Dimension::Rows,
+            String::from("3.28!Q3:Q"),
+            vec![vec![json!("25")], vec![json!(2)]],
+        );
+
+        let sample = Sample::create(
+            Input::try_from(ReadBatchResponse {
+                spreadsheet_id: String::new(),
+                value_ranges: vec![names, amounts],
+            })
+            .unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(sample.cards.get("The Doctor").unwrap().amount, 2);
+        assert_eq!(sample.cards.get("Rain of Chaos").unwrap().amount, 25);
+
+        let no_headers = ValueRange::new(
+            googlesheets::sheet::Dimension::Rows,
+            String::from("Sheet1"),
+            vec![vec![json!("Rain of Chaos"), json!(1), json!("note")]],
+        );
+        assert!(SheetInput::from_value_ranges(&[no_headers], &SheetColumns::default()).is_err());
+    }
 }
@@ -0,0 +1,77 @@
+//! Compression of stored items. Gzip and zstd are behind the `gzip` and `zstd` features.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Compression {
+    /// Plain JSON.
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn compress(self, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(bytes),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                use std::io::Write;
+                let mut encoder =
+                    flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(&bytes)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::encode_all(bytes.as_slice(), 0),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    pub fn decompress(self, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(bytes),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                use std::io::Read;
+                let mut decompressed = vec![];
+                flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed)?;
+                Ok(decompressed)
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => zstd::decode_all(bytes.as_slice()),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn unsupported(self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{self:?} compression is not enabled, see features of fs_cache_fetcher"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips() {
+        let json = br#"{"cards":["The Doctor","The Doctor","The Doctor"]}"#.to_vec();
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            match compression.compress(json.clone()) {
+                Ok(compressed) => {
+                    assert_eq!(compression.decompress(compressed).unwrap(), json);
+                }
+                Err(err) => assert_eq!(err.kind(), io::ErrorKind::Unsupported),
+            }
+        }
+    }
+}
@@ -1,8 +1,21 @@
-use std::time::{Duration, SystemTime};
+pub mod compression;
+pub mod memo;
+pub mod meta;
+pub mod snapshot;
+pub mod storage;
+pub mod variant;
+
+pub use compression::Compression;
+pub use meta::CacheMeta;
+pub use snapshot::{Retention, Snapshot};
+pub use storage::{FsStorage, MemoryStorage, Storage};
+pub use variant::CachedVariant;
+
 use std::{
-    fs::{self, File},
-    io::BufReader,
-    path::PathBuf,
+    future::Future,
+    io,
+    sync::Arc,
+    time::{Duration, SystemTime},
 };
 
 #[allow(async_fn_in_trait)]
@@ -10,9 +23,13 @@ pub trait DataFetcher
 where
     Self: Default,
 {
-    type Item: serde::Serialize + serde::de::DeserializeOwned;
-    type Error: From<serde_json::Error> + From<std::io::Error>;
-    async fn fetch(&self) -> Result<Self::Item, Self::Error>;
+    type Item: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync + 'static;
+    type Error: From<serde_json::Error> + From<std::io::Error> + std::fmt::Debug;
+    /// Version of the [`Self::Item`] format. Bump it, when the item changes incompatibly,
+    /// and cached data of previous versions is refetched.
+    const SCHEMA_VERSION: u32 = 0;
+    /// Fetches fresh data. The future is `Send`, so it can be revalidated in a spawned task.
+    fn fetch(&self) -> impl Future<Output = Result<Self::Item, Self::Error>> + Send;
     fn config(&self) -> &Config;
     fn config_mut(&mut self) -> &mut Config;
 
@@ -30,48 +47,129 @@ where
         default
     }
 
+    /// Loads the item. Stale data is never revalidated in the background here,
+    /// [`ServeStale::WhileRevalidate`] acts as [`ServeStale::OnError`].
     async fn load(&self) -> Result<Self::Item, Self::Error> {
-        let config = self.config();
-        match up_to_date(&self.file_path(), &config.stale).unwrap_or(false) {
-            true => {
-                let file = File::open(self.file_path())?;
-                let reader = BufReader::new(file);
+        Ok(self.load_with_status().await?.item)
+    }
 
-                Ok(serde_json::from_reader(reader)?)
-            }
-            false => {
-                let fetched = self.fetch().await?;
-                if config.save {
-                    self.save(&fetched)?;
+    /// Like [`DataFetcher::load`], but also reports where the item came from.
+    /// [`ServeStale::WhileRevalidate`] acts as [`ServeStale::OnError`] here,
+    /// use [`DataFetcher::load_revalidating`] to refetch in the background.
+    async fn load_with_status(&self) -> Result<Loaded<Self::Item>, Self::Error> {
+        load_memoized(self, false).await
+    }
+
+    /// Serves stale data right away with [`ServeStale::WhileRevalidate`] and refetches it
+    /// in a task spawned on the current tokio runtime.
+    async fn load_revalidating(self: Arc<Self>) -> Result<Loaded<Self::Item>, Self::Error>
+    where
+        Self: Send + Sync + 'static,
+    {
+        let loaded = load_memoized(self.as_ref(), true).await?;
+        if loaded.status == LoadStatus::StaleWhileRevalidate {
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    handle.spawn(async move {
+                        let result = match self.fetch().await {
+                            Ok(item) => store(self.as_ref(), &item),
+                            Err(err) => Err(err),
+                        };
+                        if let Err(err) = result {
+                            tracing::warn!("Revalidating {} failed: {err:?}", self.key());
+                        }
+                    });
+                }
+                Err(_) => {
+                    tracing::warn!("No tokio runtime to revalidate {}", self.key())
                 }
-                Ok(fetched)
             }
         }
+        Ok(loaded)
+    }
+
+    fn filename(&self) -> &str {
+        &self.config().filename
+    }
+
+    /// Parameters the data is fetched with, for example league or game patch.
+    /// Each variant is cached under its own [`DataFetcher::key`].
+    fn variant(&self) -> Option<String> {
+        None
     }
 
-    fn filename(&self) -> &'static str {
-        self.config().filename
+    /// Storage key of the cached item: [`DataFetcher::filename`] with the [`DataFetcher::variant`].
+    fn key(&self) -> String {
+        variant::variant_key(self.filename(), self.variant().as_deref())
     }
 
-    fn file_path(&self) -> PathBuf {
-        let dir = project_root::get_project_root()
-            .unwrap_or_else(|_| std::env::current_dir().unwrap())
-            .join("data");
-        if !dir.exists() {
-            std::fs::create_dir_all(&dir).unwrap();
+    /// Cached items of all variants of this fetcher.
+    fn cached_variants(&self) -> io::Result<Vec<CachedVariant>> {
+        let storage = self.storage();
+        let mut variants = storage
+            .keys()?
+            .into_iter()
+            .filter_map(|key| {
+                let variant = variant::parse_variant(self.filename(), &key)?;
+                Some((key, variant))
+            })
+            .map(|(key, variant)| {
+                let meta = CacheMeta::read(storage, &key).ok().flatten();
+                CachedVariant { key, variant, meta }
+            })
+            .collect::<Vec<_>>();
+        variants.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(variants)
+    }
+
+    /// Removes cached variants, that are not kept, with their metadata. Returns removed ones.
+    fn prune_variants(
+        &self,
+        keep: impl Fn(&CachedVariant) -> bool,
+    ) -> io::Result<Vec<CachedVariant>> {
+        let mut removed = vec![];
+        for cached in self.cached_variants()? {
+            if keep(&cached) {
+                continue;
+            }
+            self.storage().remove(&cached.key)?;
+            self.storage().remove(&CacheMeta::key(&cached.key))?;
+            removed.push(cached);
         }
+        Ok(removed)
+    }
+
+    fn storage(&self) -> &dyn Storage {
+        self.config().storage.as_ref()
+    }
+
+    /// Name of the fetcher in [`CacheMeta`].
+    fn fetcher_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// What the data is fetched from, for example league or game patch, recorded in [`CacheMeta`].
+    /// Unlike [`DataFetcher::variant`], it is not part of the key: cached data of another source
+    /// is replaced, not kept next to it.
+    fn source(&self) -> Option<String> {
+        None
+    }
 
-        dir.join(self.filename())
+    /// Metadata of the cached item, if there is one.
+    fn meta(&self) -> io::Result<Option<CacheMeta>> {
+        CacheMeta::read(self.storage(), &self.key())
     }
 
     fn up_to_date(&self) -> bool {
-        up_to_date(&self.file_path(), &self.config().stale).unwrap_or(false)
+        match self.meta() {
+            Ok(Some(meta)) => usable(self, &meta) && is_fresh(&meta, &self.config().stale),
+            _ => false,
+        }
     }
 
     async fn update(&self) -> Result<(), Self::Error> {
         let t = self.fetch().await?;
-        self.save(&t)?;
-        Ok(())
+        store(self, &t)
     }
 
     fn save(&self, data: &Self::Item) -> Result<(), Self::Error> {
@@ -79,41 +177,203 @@ where
             return Ok(());
         }
 
-        let json = serde_json::to_string(data)?;
-        fs::write(self.file_path(), json)?;
+        let compression = self.config().compression;
+        let bytes = compression.compress(serde_json::to_vec(data)?)?;
+        let key = self.key();
+        self.storage().write(&key, &bytes)?;
+        let meta = CacheMeta {
+            compression,
+            ..CacheMeta::new(
+                self.fetcher_name(),
+                Self::SCHEMA_VERSION,
+                self.source(),
+                &bytes,
+            )
+        };
+        meta.write(self.storage(), &key)?;
+
+        if let Some(retention) = self.config().retention {
+            snapshot::take(self.storage(), &key, &bytes, &meta, retention)?;
+        }
 
         Ok(())
     }
+
+    /// Snapshots of the item, oldest first, see [`Config::retention`].
+    fn snapshots(&self) -> io::Result<Vec<Snapshot>> {
+        snapshot::list(self.storage(), &self.key())
+    }
+
+    fn load_snapshot(&self, snapshot: &Snapshot) -> Result<Self::Item, Self::Error> {
+        let bytes = self.storage().read(&snapshot.key)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No snapshot {}", snapshot.key),
+            )
+        })?;
+        Ok(decode(bytes, &snapshot.meta)?)
+    }
+}
+
+/// Saves fetched item and drops the memoized one.
+fn store<F: DataFetcher>(fetcher: &F, item: &F::Item) -> Result<(), F::Error> {
+    fetcher.save(item)?;
+    memo::forget(&memo_key(fetcher));
+    Ok(())
+}
+
+async fn load_memoized<F: DataFetcher>(
+    fetcher: &F,
+    revalidate_later: bool,
+) -> Result<Loaded<F::Item>, F::Error> {
+    match fetcher.config().memo_ttl {
+        Some(ttl) => {
+            memo::get_or_load(memo_key(fetcher), ttl, || load(fetcher, revalidate_later)).await
+        }
+        None => load(fetcher, revalidate_later).await,
+    }
+}
+
+/// Fetcher type, [`Storage::id`] and key, so fetchers with different storages do not share items.
+fn memo_key<F: DataFetcher>(fetcher: &F) -> String {
+    format!(
+        "{}:{}:{}",
+        fetcher.fetcher_name(),
+        fetcher.storage().id(),
+        fetcher.key()
+    )
 }
 
-#[derive(Debug)]
-pub struct FileNotExists(pub PathBuf);
-fn up_to_date(path: &PathBuf, stale: &Stale) -> Result<bool, FileNotExists> {
-    if !path.exists() {
-        return Ok(false);
+async fn load<F: DataFetcher>(
+    fetcher: &F,
+    revalidate_later: bool,
+) -> Result<Loaded<F::Item>, F::Error> {
+    let config = fetcher.config();
+    let meta = fetcher
+        .meta()
+        .ok()
+        .flatten()
+        .filter(|meta| usable(fetcher, meta));
+
+    if let Some(meta) = &meta {
+        let status = if is_fresh(meta, &config.stale) {
+            Some(LoadStatus::Cached)
+        } else if revalidate_later && config.serve_stale == ServeStale::WhileRevalidate {
+            Some(LoadStatus::StaleWhileRevalidate)
+        } else {
+            None
+        };
+        if let Some(status) = status {
+            if let Some(item) = read(fetcher, meta)? {
+                return Ok(Loaded { item, status });
+            }
+        }
     }
 
-    match stale {
-        Stale::Never => Ok(true),
-        Stale::After(stale_after) => {
-            let file_not_exists = |_| FileNotExists(path.to_owned());
+    match fetcher.fetch().await {
+        Ok(fetched) => {
+            fetcher.save(&fetched)?;
+            Ok(Loaded {
+                item: fetched,
+                status: LoadStatus::Fetched,
+            })
+        }
+        Err(err) => match &meta {
+            Some(meta) if config.serve_stale != ServeStale::Never => match read(fetcher, meta)? {
+                Some(item) => {
+                    tracing::warn!(
+                        "Fetching {} failed, serving stale data: {err:?}",
+                        fetcher.key()
+                    );
+                    Ok(Loaded {
+                        item,
+                        status: LoadStatus::StaleOnError {
+                            error: format!("{err:?}"),
+                        },
+                    })
+                }
+                None => Err(err),
+            },
+            _ => Err(err),
+        },
+    }
+}
+
+/// Reads the cached item. Corrupt item is reported with a warning and treated as missing.
+fn read<F: DataFetcher>(fetcher: &F, meta: &CacheMeta) -> Result<Option<F::Item>, F::Error> {
+    let Some(bytes) = fetcher.storage().read(&fetcher.key())? else {
+        return Ok(None);
+    };
 
-            let last_modified = fs::metadata(path)
-                .map_err(file_not_exists)?
-                .modified()
-                .map_err(file_not_exists)?;
-            let until = last_modified + *stale_after;
-            Ok(until > SystemTime::now())
+    match decode(bytes, meta) {
+        Ok(item) => Ok(Some(item)),
+        Err(err) => {
+            tracing::warn!("Cached {} is corrupt, refetching: {err}", fetcher.key());
+            Ok(None)
         }
-        Stale::ReloadEveryTime => Ok(false),
     }
 }
 
-#[derive(Default, Clone)]
+fn decode<T: serde::de::DeserializeOwned>(bytes: Vec<u8>, meta: &CacheMeta) -> io::Result<T> {
+    if meta::content_hash(&bytes) != meta.content_hash {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "checksum mismatch",
+        ));
+    }
+    let json = meta.compression.decompress(bytes)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Cached item is of the current schema and source, and is not older than [`Config::max_age`].
+fn usable<F: DataFetcher>(fetcher: &F, meta: &CacheMeta) -> bool {
+    meta.schema_version == F::SCHEMA_VERSION
+        && meta.source == fetcher.source()
+        && fetcher
+            .config()
+            .max_age
+            .is_none_or(|max_age| meta.fetched_at() + max_age > SystemTime::now())
+}
+
+fn is_fresh(meta: &CacheMeta, stale: &Stale) -> bool {
+    match stale {
+        Stale::Never => true,
+        Stale::After(stale_after) => meta.fetched_at() + *stale_after > SystemTime::now(),
+        Stale::ReloadEveryTime => false,
+    }
+}
+
+#[derive(Clone)]
 pub struct Config {
     pub save: bool,
-    pub filename: &'static str,
+    pub filename: String,
     pub stale: Stale,
+    /// Where data is cached, user cache dir by default.
+    pub storage: Arc<dyn Storage>,
+    pub serve_stale: ServeStale,
+    /// Cached data older than this is never served, even with [`Stale::Never`] or [`ServeStale`].
+    pub max_age: Option<Duration>,
+    pub compression: Compression,
+    /// Keep loaded items in memory for this long, see [`memo`]. Disabled by default.
+    pub memo_ttl: Option<Duration>,
+    /// Keep snapshots of saved items, see [`snapshot`]. Disabled by default.
+    pub retention: Option<Retention>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            save: false,
+            filename: String::new(),
+            stale: Stale::default(),
+            storage: Arc::new(FsStorage::default()),
+            serve_stale: ServeStale::default(),
+            max_age: None,
+            compression: Compression::default(),
+            memo_ttl: None,
+            retention: None,
+        }
+    }
 }
 
 impl Config {
@@ -131,8 +391,38 @@ impl Config {
         self
     }
 
-    pub fn filename(&mut self, filename: &'static str) -> &mut Self {
-        self.filename = filename;
+    pub fn filename(&mut self, filename: impl Into<String>) -> &mut Self {
+        self.filename = filename.into();
+        self
+    }
+
+    pub fn storage(&mut self, storage: impl Storage + 'static) -> &mut Self {
+        self.storage = Arc::new(storage);
+        self
+    }
+
+    pub fn serve_stale(&mut self, serve_stale: ServeStale) -> &mut Self {
+        self.serve_stale = serve_stale;
+        self
+    }
+
+    pub fn max_age(&mut self, max_age: Option<Duration>) -> &mut Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn compression(&mut self, compression: Compression) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn memo_ttl(&mut self, memo_ttl: Option<Duration>) -> &mut Self {
+        self.memo_ttl = memo_ttl;
+        self
+    }
+
+    pub fn retention(&mut self, retention: Option<Retention>) -> &mut Self {
+        self.retention = retention;
         self
     }
 }
@@ -145,11 +435,52 @@ pub enum Stale {
     ReloadEveryTime,
 }
 
+/// What to do with cached data, that is [`Stale`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ServeStale {
+    /// Refetch, fail if fetch fails.
+    #[default]
+    Never,
+    /// Refetch, serve stale data with a warning if fetch fails.
+    OnError,
+    /// Serve stale data and refetch in the background, see [`DataFetcher::load_revalidating`].
+    /// Other loads treat it as [`ServeStale::OnError`].
+    WhileRevalidate,
+}
+
+/// Item and how it was loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loaded<T> {
+    pub item: T,
+    pub status: LoadStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadStatus {
+    /// Fresh data from the storage.
+    Cached,
+    /// Item kept in memory, see [`Config::memo_ttl`].
+    Memoized,
+    Fetched,
+    /// Fetch failed, stale data is served.
+    StaleOnError {
+        error: String,
+    },
+    /// Stale data is served, refetch is running in the background.
+    StaleWhileRevalidate,
+}
+
 #[derive(Default)]
 pub struct ConfigBuilder {
     pub save: bool,
-    pub filename: &'static str,
+    pub filename: String,
     pub stale: Stale,
+    pub storage: Option<Arc<dyn Storage>>,
+    pub serve_stale: ServeStale,
+    pub max_age: Option<Duration>,
+    pub compression: Compression,
+    pub memo_ttl: Option<Duration>,
+    pub retention: Option<Retention>,
 }
 
 impl ConfigBuilder {
@@ -163,8 +494,38 @@ impl ConfigBuilder {
         self
     }
 
-    pub fn filename(mut self, filename: &'static str) -> Self {
-        self.filename = filename;
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = filename.into();
+        self
+    }
+
+    pub fn storage(mut self, storage: impl Storage + 'static) -> Self {
+        self.storage = Some(Arc::new(storage));
+        self
+    }
+
+    pub fn serve_stale(mut self, serve_stale: ServeStale) -> Self {
+        self.serve_stale = serve_stale;
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn memo_ttl(mut self, memo_ttl: Duration) -> Self {
+        self.memo_ttl = Some(memo_ttl);
+        self
+    }
+
+    pub fn retention(mut self, retention: Retention) -> Self {
+        self.retention = Some(retention);
         self
     }
 
@@ -173,6 +534,292 @@ impl ConfigBuilder {
             save: self.save,
             filename: self.filename,
             stale: self.stale,
+            storage: self
+                .storage
+                .unwrap_or_else(|| Arc::new(FsStorage::default())),
+            serve_stale: self.serve_stale,
+            max_age: self.max_age,
+            compression: self.compression,
+            memo_ttl: self.memo_ttl,
+            retention: self.retention,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    struct CountingFetcher {
+        config: Config,
+        fetches: AtomicU32,
+        fail: AtomicBool,
+        league: Option<String>,
+    }
+
+    impl Default for CountingFetcher {
+        fn default() -> Self {
+            CountingFetcher {
+                config: Config::builder()
+                    .save(true)
+                    .filename("count.json")
+                    .stale(Stale::After(Duration::from_secs(60)))
+                    .storage(MemoryStorage::new())
+                    .build(),
+                fetches: AtomicU32::new(0),
+                fail: AtomicBool::new(false),
+                league: None,
+            }
+        }
+    }
+
+    impl DataFetcher for CountingFetcher {
+        type Item = u32;
+        type Error = io::Error;
+
+        async fn fetch(&self) -> Result<u32, io::Error> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(io::Error::other("offline"));
+            }
+            Ok(self.fetches.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn config(&self) -> &Config {
+            &self.config
+        }
+
+        fn config_mut(&mut self) -> &mut Config {
+            &mut self.config
+        }
+
+        fn variant(&self) -> Option<String> {
+            self.league.clone()
         }
     }
+
+    #[tokio::test]
+    async fn loads_from_memory_storage() {
+        let storage = Arc::new(MemoryStorage::new());
+        let fetcher = CountingFetcher::default_with_mut_config(|config| {
+            config.storage(storage.clone());
+        });
+
+        assert_eq!(fetcher.load().await.unwrap(), 1);
+        assert_eq!(fetcher.load().await.unwrap(), 1);
+        assert_eq!(storage.read("count.json").unwrap(), Some(b"1".to_vec()));
+
+        let mut meta = fetcher.meta().unwrap().unwrap();
+        assert_eq!(meta.content_hash, meta::content_hash(b"1"));
+        meta.fetched_at -= 120;
+        meta.write(storage.as_ref(), "count.json").unwrap();
+        assert_eq!(fetcher.load().await.unwrap(), 2);
+
+        let mut meta = fetcher.meta().unwrap().unwrap();
+        meta.schema_version += 1;
+        meta.write(storage.as_ref(), "count.json").unwrap();
+        assert!(!fetcher.up_to_date());
+        assert_eq!(fetcher.load().await.unwrap(), 3);
+    }
+
+    fn age(fetcher: &CountingFetcher, secs: u64) {
+        let mut meta = fetcher.meta().unwrap().unwrap();
+        meta.fetched_at -= secs;
+        meta.write(fetcher.storage(), &fetcher.key()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn serves_stale_on_error() {
+        let fetcher = CountingFetcher::default_with_mut_config(|config| {
+            config
+                .storage(MemoryStorage::new())
+                .serve_stale(ServeStale::OnError)
+                .max_age(Some(Duration::from_secs(3600)));
+        });
+        assert_eq!(
+            fetcher.load_with_status().await.unwrap().status,
+            LoadStatus::Fetched
+        );
+        assert_eq!(
+            fetcher.load_with_status().await.unwrap().status,
+            LoadStatus::Cached
+        );
+
+        age(&fetcher, 120);
+        fetcher.fail.store(true, Ordering::SeqCst);
+        let loaded = fetcher.load_with_status().await.unwrap();
+        assert_eq!(loaded.item, 1);
+        assert!(matches!(loaded.status, LoadStatus::StaleOnError { .. }));
+
+        age(&fetcher, 7200);
+        assert!(fetcher.load().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn revalidates_in_background() {
+        let fetcher = Arc::new(CountingFetcher::default_with_mut_config(|config| {
+            config
+                .storage(MemoryStorage::new())
+                .serve_stale(ServeStale::WhileRevalidate);
+        }));
+        fetcher.load().await.unwrap();
+        age(&fetcher, 120);
+
+        let loaded = fetcher.clone().load_revalidating().await.unwrap();
+        assert_eq!(
+            loaded,
+            Loaded {
+                item: 1,
+                status: LoadStatus::StaleWhileRevalidate
+            }
+        );
+
+        for _ in 0..100 {
+            if fetcher.up_to_date() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            fetcher.load_with_status().await.unwrap(),
+            Loaded {
+                item: 2,
+                status: LoadStatus::Cached
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_and_prunes_variants() {
+        let storage = Arc::new(MemoryStorage::new());
+        for league in [None, Some("Settlers"), Some("Keepers of the Flame")] {
+            let mut fetcher = CountingFetcher::default_with_mut_config(|config| {
+                config.storage(storage.clone());
+            });
+            fetcher.league = league.map(str::to_owned);
+            fetcher.load().await.unwrap();
+        }
+
+        let fetcher = CountingFetcher::default_with_mut_config(|config| {
+            config.storage(storage.clone());
+        });
+        let keys = fetcher
+            .cached_variants()
+            .unwrap()
+            .into_iter()
+            .map(|cached| cached.key)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "count.Keepers-of-the-Flame.json",
+                "count.Settlers.json",
+                "count.json"
+            ]
+        );
+
+        let removed = fetcher
+            .prune_variants(|cached| cached.variant.is_none())
+            .unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(removed.iter().all(|cached| cached.meta.is_some()));
+        assert_eq!(storage.keys().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn refetches_corrupt_entry() {
+        let storage = Arc::new(MemoryStorage::new());
+        let fetcher = CountingFetcher::default_with_mut_config(|config| {
+            config
+                .storage(storage.clone())
+                .stale(Stale::Never)
+                .compression(Compression::Gzip);
+        });
+        assert_eq!(fetcher.load().await.unwrap(), 1);
+        assert_eq!(fetcher.load().await.unwrap(), 1);
+
+        let mut bytes = storage.read("count.json").unwrap().unwrap();
+        bytes.truncate(bytes.len() / 2);
+        storage.write("count.json", &bytes).unwrap();
+        assert_eq!(
+            fetcher.load_with_status().await.unwrap(),
+            Loaded {
+                item: 2,
+                status: LoadStatus::Fetched
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn coalesces_concurrent_loads() {
+        let fetcher = CountingFetcher::default_with_mut_config(|config| {
+            config
+                .storage(MemoryStorage::new())
+                .stale(Stale::ReloadEveryTime)
+                .memo_ttl(Some(Duration::from_secs(60)));
+        });
+
+        let (a, b) = tokio::join!(fetcher.load_with_status(), fetcher.load_with_status());
+        let mut statuses = [a.unwrap().status, b.unwrap().status];
+        statuses.sort_by_key(|status| *status == LoadStatus::Memoized);
+        assert_eq!(statuses, [LoadStatus::Fetched, LoadStatus::Memoized]);
+        assert_eq!(fetcher.fetches.load(Ordering::SeqCst), 1);
+
+        fetcher.update().await.unwrap();
+        assert_eq!(fetcher.load().await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn shares_memo_between_fetchers_of_one_storage() {
+        let dir =
+            std::env::temp_dir().join(format!("fs_cache_fetcher_memo_{}", std::process::id()));
+        let fetcher = || {
+            CountingFetcher::default_with_mut_config(|config| {
+                config
+                    .storage(FsStorage::new(&dir))
+                    .stale(Stale::ReloadEveryTime)
+                    .memo_ttl(Some(Duration::from_secs(60)));
+            })
+        };
+
+        let (a, b) = (fetcher(), fetcher());
+        assert_eq!(
+            a.load_with_status().await.unwrap().status,
+            LoadStatus::Fetched
+        );
+        assert_eq!(
+            b.load_with_status().await.unwrap(),
+            Loaded {
+                item: 1,
+                status: LoadStatus::Memoized
+            }
+        );
+        assert_eq!(b.fetches.load(Ordering::SeqCst), 0);
+
+        let other_storage = CountingFetcher::default_with_mut_config(|config| {
+            config
+                .storage(MemoryStorage::new())
+                .memo_ttl(Some(Duration::from_secs(60)));
+        });
+        assert_eq!(
+            other_storage.load_with_status().await.unwrap().status,
+            LoadStatus::Fetched
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn loads_snapshot() {
+        let fetcher = CountingFetcher::default_with_mut_config(|config| {
+            config
+                .storage(MemoryStorage::new())
+                .retention(Some(Retention::Last(2)));
+        });
+        assert!(fetcher.snapshots().unwrap().is_empty());
+        fetcher.load().await.unwrap();
+
+        let snapshots = fetcher.snapshots().unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(fetcher.load_snapshot(&snapshots[0]).unwrap(), 1);
+    }
 }
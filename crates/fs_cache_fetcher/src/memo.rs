@@ -0,0 +1,83 @@
+//! Process-wide memoization of loaded items, see [`crate::Config::memo_ttl`].
+//!
+//! Concurrent loads of the same item wait for the first one, and loads within the TTL
+//! get a clone of the item without touching the storage or network.
+
+use crate::{LoadStatus, Loaded};
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+type Slot = Arc<tokio::sync::Mutex<Option<Entry>>>;
+
+struct Entry {
+    item: Arc<dyn Any + Send + Sync>,
+    expires_at: Instant,
+}
+
+fn slots() -> std::sync::MutexGuard<'static, HashMap<String, Slot>> {
+    static SLOTS: OnceLock<Mutex<HashMap<String, Slot>>> = OnceLock::new();
+    SLOTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub(crate) async fn get_or_load<T, E, Fut>(
+    key: String,
+    ttl: Duration,
+    load: impl FnOnce() -> Fut,
+) -> Result<Loaded<T>, E>
+where
+    T: Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<Loaded<T>, E>>,
+{
+    let slot = {
+        let mut slots = slots();
+        evict_expired(&mut slots);
+        slots.entry(key).or_default().clone()
+    };
+    let mut entry = slot.lock().await;
+    if let Some(Entry { item, expires_at }) = entry.as_ref() {
+        if let Some(item) = item.downcast_ref::<T>() {
+            if Instant::now() < *expires_at {
+                return Ok(Loaded {
+                    item: item.clone(),
+                    status: LoadStatus::Memoized,
+                });
+            }
+        }
+    }
+
+    let loaded = load().await?;
+    *entry = Some(Entry {
+        item: Arc::new(loaded.item.clone()),
+        expires_at: Instant::now() + ttl,
+    });
+    Ok(loaded)
+}
+
+/// Removes expired and empty slots, that no load is waiting for.
+fn evict_expired(slots: &mut HashMap<String, Slot>) {
+    let now = Instant::now();
+    slots.retain(|_, slot| {
+        Arc::strong_count(slot) > 1
+            || slot
+                .try_lock()
+                .is_ok_and(|entry| entry.as_ref().is_some_and(|entry| entry.expires_at > now))
+    });
+}
+
+/// Drops the memoized item, next load goes to the storage or fetches.
+pub fn forget(key: &str) {
+    slots().remove(key);
+}
+
+/// Drops all memoized items.
+pub fn clear() {
+    slots().clear();
+}
@@ -0,0 +1,75 @@
+//! Metadata record, stored next to every cached item.
+//!
+//! Freshness is decided from the record, not from file modification time,
+//! so copied caches, git checkouts and clock skew of the filesystem do not matter.
+
+use crate::{compression::Compression, storage::Storage};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheMeta {
+    /// Unix time in seconds.
+    pub fetched_at: u64,
+    pub fetcher: String,
+    pub schema_version: u32,
+    /// What the data was fetched from, for example league or game patch.
+    pub source: Option<String>,
+    /// [`content_hash`] of stored bytes, verified on load.
+    pub content_hash: String,
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+impl CacheMeta {
+    pub fn new(
+        fetcher: impl Into<String>,
+        schema_version: u32,
+        source: Option<String>,
+        content: &[u8],
+    ) -> Self {
+        CacheMeta {
+            fetched_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            fetcher: fetcher.into(),
+            schema_version,
+            source,
+            content_hash: content_hash(content),
+            compression: Compression::None,
+        }
+    }
+
+    pub fn fetched_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.fetched_at)
+    }
+
+    /// Storage key of the record for cached item `key`.
+    pub fn key(key: &str) -> String {
+        format!("{key}.meta.json")
+    }
+
+    pub fn read(storage: &dyn Storage, key: &str) -> io::Result<Option<CacheMeta>> {
+        match storage.read(&CacheMeta::key(key))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn write(&self, storage: &dyn Storage, key: &str) -> io::Result<()> {
+        storage.write(&CacheMeta::key(key), &serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// FNV-1a 64 of the bytes as hex. Detects changed content, not meant to be cryptographic.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
@@ -0,0 +1,135 @@
+//! Snapshots of saved items, kept by [`Retention`] next to the cached item.
+//!
+//! Snapshot of `key` is stored under `snapshots/{key}/{fetched_at}`,
+//! with an index of all snapshots of the key in `snapshots/{key}/index.json`.
+
+use crate::{meta::CacheMeta, storage::Storage};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    time::{Duration, SystemTime},
+};
+
+/// How many snapshots of every key to keep. The latest one is always kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    Last(usize),
+    Days(u32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Storage key of the snapshot.
+    pub key: String,
+    pub meta: CacheMeta,
+}
+
+impl Snapshot {
+    pub fn taken_at(&self) -> SystemTime {
+        self.meta.fetched_at()
+    }
+}
+
+fn index_key(key: &str) -> String {
+    format!("snapshots/{key}/index.json")
+}
+
+/// Snapshots of the key, oldest first.
+pub fn list(storage: &dyn Storage, key: &str) -> io::Result<Vec<Snapshot>> {
+    match storage.read(&index_key(key))? {
+        Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        None => Ok(vec![]),
+    }
+}
+
+/// Stores the saved item as a snapshot and drops the ones, that are not retained.
+pub(crate) fn take(
+    storage: &dyn Storage,
+    key: &str,
+    bytes: &[u8],
+    meta: &CacheMeta,
+    retention: Retention,
+) -> io::Result<()> {
+    let snapshot = Snapshot {
+        key: format!("snapshots/{key}/{}", meta.fetched_at),
+        meta: meta.clone(),
+    };
+    storage.write(&snapshot.key, bytes)?;
+
+    let mut snapshots = list(storage, key)?;
+    snapshots.retain(|existing| existing.key != snapshot.key);
+    snapshots.push(snapshot);
+
+    let expired = expired(&snapshots, retention, SystemTime::now());
+    for snapshot in &snapshots[..expired] {
+        storage.remove(&snapshot.key)?;
+    }
+    storage.write(
+        &index_key(key),
+        &serde_json::to_vec_pretty(&snapshots[expired..])?,
+    )
+}
+
+/// Number of the oldest snapshots, that are not retained.
+fn expired(snapshots: &[Snapshot], retention: Retention, now: SystemTime) -> usize {
+    let expired = match retention {
+        Retention::Last(n) => snapshots.len().saturating_sub(n),
+        Retention::Days(days) => {
+            let since = now - Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+            snapshots
+                .iter()
+                .take_while(|snapshot| snapshot.taken_at() < since)
+                .count()
+        }
+    };
+    expired.min(snapshots.len().saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn meta(fetched_at: u64) -> CacheMeta {
+        CacheMeta {
+            fetched_at,
+            ..CacheMeta::new("test", 0, None, b"")
+        }
+    }
+
+    #[test]
+    fn retains_last() {
+        let storage = MemoryStorage::new();
+        for fetched_at in [100, 200, 300] {
+            take(
+                &storage,
+                "a.json",
+                b"1",
+                &meta(fetched_at),
+                Retention::Last(2),
+            )
+            .unwrap();
+        }
+
+        let keys = list(&storage, "a.json")
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["snapshots/a.json/200", "snapshots/a.json/300"]);
+        assert_eq!(storage.read("snapshots/a.json/100").unwrap(), None);
+    }
+
+    #[test]
+    fn retains_days() {
+        let day = 24 * 60 * 60;
+        let snapshots = [1, 5, 9].map(|days| Snapshot {
+            key: days.to_string(),
+            meta: meta(days * day),
+        });
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(10 * day);
+        assert_eq!(expired(&snapshots, Retention::Days(3), now), 2);
+        assert_eq!(expired(&snapshots, Retention::Days(0), now), 2);
+        assert_eq!(expired(&snapshots, Retention::Days(30), now), 0);
+    }
+}
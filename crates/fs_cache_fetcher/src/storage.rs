@@ -0,0 +1,232 @@
+//! Where fetched data is cached.
+//!
+//! [`FsStorage`] keeps files in a directory, [`MemoryStorage`] keeps bytes in the process.
+//! Anything else (browser storage, a database, app data dir of the Tauri app) can implement [`Storage`].
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Directory name inside of the user cache dir for [`FsStorage::cache_dir`].
+pub const CACHE_DIR_NAME: &str = "fs_cache_fetcher";
+
+/// Key-value storage of cached data. Key is the [`crate::Config::filename`].
+pub trait Storage: Debug + Send + Sync {
+    /// Returns `None` if there is nothing stored under the key.
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+    fn remove(&self, key: &str) -> io::Result<()>;
+    /// All stored keys, in no particular order.
+    fn keys(&self) -> io::Result<Vec<String>>;
+    /// Identity of the stored data, for example the directory. Fetchers with storages
+    /// of the same id share memoized items, see [`crate::memo`].
+    fn id(&self) -> String;
+}
+
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        (**self).read(key)
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        (**self).write(key, bytes)
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        (**self).remove(key)
+    }
+
+    fn keys(&self) -> io::Result<Vec<String>> {
+        (**self).keys()
+    }
+
+    fn id(&self) -> String {
+        (**self).id()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Root {
+    Dir(PathBuf),
+    CacheDir,
+    ProjectData,
+}
+
+/// Files in a directory. The directory is resolved and created on first write.
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    root: Root,
+}
+
+impl Default for FsStorage {
+    fn default() -> Self {
+        FsStorage::cache_dir()
+    }
+}
+
+impl FsStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FsStorage {
+            root: Root::Dir(root.into()),
+        }
+    }
+
+    /// User cache dir: `$XDG_CACHE_HOME/fs_cache_fetcher` or `~/.cache/fs_cache_fetcher` on Linux,
+    /// `~/Library/Caches` and `%LOCALAPPDATA%` on macOS and Windows.
+    pub fn cache_dir() -> Self {
+        FsStorage {
+            root: Root::CacheDir,
+        }
+    }
+
+    /// `data` dir of the project root, or of the current dir, when there is no project.
+    /// Handy for the tools of this workspace, that commit or publish the data.
+    pub fn project_data() -> Self {
+        FsStorage {
+            root: Root::ProjectData,
+        }
+    }
+
+    pub fn root(&self) -> io::Result<PathBuf> {
+        match &self.root {
+            Root::Dir(dir) => Ok(dir.clone()),
+            Root::CacheDir => dirs::cache_dir()
+                .map(|dir| dir.join(CACHE_DIR_NAME))
+                .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "No user cache directory")),
+            Root::ProjectData => {
+                let dir = match project_root::get_project_root() {
+                    Ok(dir) => dir,
+                    Err(_) => std::env::current_dir()?,
+                };
+                Ok(dir.join("data"))
+            }
+        }
+    }
+
+    pub fn path(&self, key: &str) -> io::Result<PathBuf> {
+        Ok(self.root()?.join(key))
+    }
+}
+
+impl Storage for FsStorage {
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes into a temp file next to the target and renames it, so an interrupted write
+    /// never leaves a truncated file behind.
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = path.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+        if let Err(err) = fs::write(&tmp, bytes).and_then(|_| fs::rename(&tmp, &path)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn keys(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(self.root()?) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+
+        let mut keys = vec![];
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                if let Some(name) = entry.file_name().to_str() {
+                    keys.push(name.to_owned());
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// The directory, or the kind of the root if it can not be resolved.
+    fn id(&self) -> String {
+        match self.root() {
+            Ok(root) => root.display().to_string(),
+            Err(_) => format!("{:?}", self.root),
+        }
+    }
+}
+
+/// Storage, that lives as long as the process. For tests and for targets without filesystem.
+#[derive(Debug)]
+pub struct MemoryStorage {
+    /// Every instance is a storage of its own.
+    id: u64,
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        MemoryStorage {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            entries: Mutex::default(),
+        }
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<u8>>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.lock().get(key).cloned())
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.lock().insert(key.to_owned(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> io::Result<()> {
+        self.lock().remove(key);
+        Ok(())
+    }
+
+    fn keys(&self) -> io::Result<Vec<String>> {
+        Ok(self.lock().keys().cloned().collect())
+    }
+
+    fn id(&self) -> String {
+        format!("memory:{}", self.id)
+    }
+}
@@ -0,0 +1,96 @@
+//! Cache keys of one fetcher, fetched with different parameters (league, game patch, spreadsheet id).
+//!
+//! Variant goes before the extensions of [`crate::Config::filename`]:
+//! `cards.json` without a variant, `cards.cdn-3.29.0.json` with `cdn-3.29.0`,
+//! `spreadsheet.cdn-3.29.0.json.gz` for a compressed item.
+
+use crate::meta::CacheMeta;
+
+/// Cached item of one variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedVariant {
+    /// Storage key.
+    pub key: String,
+    pub variant: Option<String>,
+    pub meta: Option<CacheMeta>,
+}
+
+/// Storage key of the variant of `filename`.
+pub fn variant_key(filename: &str, variant: Option<&str>) -> String {
+    let Some(variant) = variant else {
+        return filename.to_owned();
+    };
+    let (stem, ext) = split_extension(filename);
+    format!("{stem}.{}{ext}", sanitize(variant))
+}
+
+/// Variant of `filename` the key is stored under: `Some(None)` for the key without a variant,
+/// `None` if the key is not a variant of `filename`.
+pub fn parse_variant(filename: &str, key: &str) -> Option<Option<String>> {
+    if key == filename {
+        return Some(None);
+    }
+    if key.ends_with(CacheMeta::key("").as_str()) {
+        return None;
+    }
+
+    let (stem, ext) = split_extension(filename);
+    let variant = key
+        .strip_prefix(stem)?
+        .strip_prefix('.')?
+        .strip_suffix(ext)?;
+    if variant.is_empty() {
+        None
+    } else {
+        Some(Some(variant.to_owned()))
+    }
+}
+
+/// `("cards", ".json")` for `cards.json`, `("spreadsheet", ".json.gz")` for `spreadsheet.json.gz`.
+fn split_extension(filename: &str) -> (&str, &str) {
+    match filename.get(1..).and_then(|rest| rest.find('.')) {
+        Some(index) => filename.split_at(index + 1),
+        None => (filename, ""),
+    }
+}
+
+/// Keeps variant usable as a file name.
+fn sanitize(variant: &str) -> String {
+    variant
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_keys() {
+        assert_eq!(variant_key("cards.json", None), "cards.json");
+        assert_eq!(
+            variant_key("cards.json", Some("cdn:3.29.0")),
+            "cards.cdn-3.29.0.json"
+        );
+        assert_eq!(variant_key("prices", Some("Settlers")), "prices.Settlers");
+        assert_eq!(
+            variant_key("spreadsheet.json.gz", Some("Settlers")),
+            "spreadsheet.Settlers.json.gz"
+        );
+
+        assert_eq!(parse_variant("cards.json", "cards.json"), Some(None));
+        assert_eq!(
+            parse_variant("cards.json", "cards.cdn-3.29.0.json"),
+            Some(Some("cdn-3.29.0".to_owned()))
+        );
+        assert_eq!(parse_variant("cards.json", "cards.json.meta.json"), None);
+        assert_eq!(parse_variant("cards.json", "cardElementData.json"), None);
+    }
+}
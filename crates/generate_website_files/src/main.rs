@@ -6,7 +6,11 @@ use std::{
 };
 
 use card_element::DivinationCardElementData;
-use divcord::{spreadsheet::Spreadsheet, ParseRecordError, PoeData, Record, Source};
+use divcord::{
+    lint::{LintCode, LintConfig, LintReport},
+    spreadsheet::Spreadsheet,
+    ParseRecordError, PoeData, Record, Source,
+};
 use fs_cache_fetcher::DataFetcher;
 use poe_data::fetchers::{CardElementsFetcher, PoeDataFetcher};
 use serde::Serialize;
@@ -16,7 +20,7 @@ use serde::Serialize;
 #[tokio::main]
 async fn main() {
     // Load poeData (runs the extraction pipeline when stale)
-    let poe_data: PoeData = PoeDataFetcher::default().load().await.unwrap();
+    let mut poe_data: PoeData = PoeDataFetcher::default().load().await.unwrap();
 
     // Load cardElementData
     let card_element: Vec<DivinationCardElementData> =
@@ -60,7 +64,20 @@ async fn main() {
 
     // ── Spreadsheet (still fetched live) ──────────────────────
     dotenv::dotenv().ok();
+    // Sources of new league content, that are not built into divcord yet.
+    // Written into poeData.json, so that divcord_wasm registers them too.
+    if let Ok(json) = std::fs::read_to_string("extra_sources.json") {
+        poe_data.extra_sources = serde_json::from_str(&json).unwrap();
+        poe_data.register_extra_sources();
+        println!("Registered {} extra sources", poe_data.extra_sources.len());
+    }
     let spreadsheet = Spreadsheet::load().await.unwrap();
+    if !spreadsheet.missing_columns.is_empty() {
+        println!(
+            "Columns not found in the header row, default letters are used: {}",
+            spreadsheet.missing_columns.join(", ")
+        );
+    }
     let records = parse_divcord_records(&spreadsheet, &poe_data);
 
     if !dir.exists() {
@@ -73,7 +90,13 @@ async fn main() {
     let mut sources_hashmap: HashMap<String, Source> = records
         .clone()
         .into_iter()
-        .flat_map(|record| record.sources.into_iter().chain(record.verify_sources))
+        .flat_map(|record| {
+            record
+                .sources
+                .into_iter()
+                .chain(record.verify_sources)
+                .chain(record.datamined_sources)
+        })
         .collect::<HashSet<Source>>()
         .into_iter()
         .map(|source| (source.slug(), source))
@@ -107,26 +130,25 @@ async fn main() {
 
 fn parse_divcord_records(spreadsheet: &Spreadsheet, poe_data: &PoeData) -> Vec<Record> {
     println!("Parse divcord records");
-    let on_err = |s: &str| {
-        println!("Error parsing divcord records: {s}");
-    };
-
     let mut records: Vec<Record> = vec![];
+    let mut errors: Vec<ParseRecordError> = vec![];
     for record in divcord::records_iter(spreadsheet, poe_data) {
         match record {
             Ok(record_result) => {
                 records.push(record_result.record);
                 if !record_result.errors.is_empty() {
-                    let errors_string =
-                        ParseRecordError::ParseDropSources(record_result.errors).to_string();
-                    on_err(&errors_string);
+                    errors.push(ParseRecordError::ParseDropSources(record_result.errors));
                 }
             }
-            Err(err) => {
-                on_err(&err.to_string());
-            }
+            Err(err) => errors.push(ParseRecordError::ParseDumb(err)),
         }
     }
+
+    let config = LintConfig::default().warn(LintCode::ActsMustBeItalic);
+    let report = LintReport::new(&errors, &spreadsheet.layout, &config);
+    if !report.lints.is_empty() {
+        println!("Divcord spreadsheet lints:\n{}", report.to_markdown());
+    }
     records
 }
 
@@ -0,0 +1,336 @@
+//! Credentials for Google Sheets API requests.
+//!
+//! Every sheet function takes a [`CredentialProvider`]. Plain [`Credential`] is a provider itself,
+//! [`ServiceAccount`] and [`RefreshToken`] obtain access tokens on their own and cache them until expiry,
+//! so unattended jobs can keep writing sheets without user interaction.
+//!
+//! [`ServiceAccount`] signs JWTs and needs the `service-account` feature, which is off by default
+//! to keep the signing crates out of wasm builds. Self-refreshing credentials are not available
+//! in wasm: there is no monotonic clock to track the token expiry with in the browser.
+
+use crate::{error::Error, sheet::Credential};
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+pub const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+pub const SPREADSHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Token is considered expired a bit earlier, so it does not run out in the middle of a request.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+/// Google does not accept service account assertions valid for more than an hour.
+const ASSERTION_LIFETIME_SECS: u64 = 3600;
+
+/// Source of [`Credential`] for sheet requests. `http` is the client of the [`crate::SheetsClient`]
+/// that sends the request, providers use it to obtain tokens.
+pub trait CredentialProvider: Send + Sync {
+    fn credential(&self, http: &Client) -> impl Future<Output = Result<Credential, Error>> + Send;
+}
+
+impl CredentialProvider for Credential {
+    async fn credential(&self, _http: &Client) -> Result<Credential, Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<P: CredentialProvider> CredentialProvider for &P {
+    fn credential(&self, http: &Client) -> impl Future<Output = Result<Credential, Error>> + Send {
+        (**self).credential(http)
+    }
+}
+
+/// Error body of OAuth token endpoint.
+#[derive(Debug, Serialize, Clone, Deserialize)]
+pub struct TokenError {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{}: {description}", self.error),
+            None => f.write_str(&self.error),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// Access token storage shared by self-refreshing credentials.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+struct TokenCache(Mutex<Option<CachedToken>>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TokenCache {
+    fn get(&self) -> Option<String> {
+        let guard = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        guard
+            .as_ref()
+            .filter(|token| token.expires_at > Instant::now() + EXPIRY_MARGIN)
+            .map(|token| token.access_token.clone())
+    }
+
+    fn set(&self, response: &TokenResponse) {
+        let mut guard = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *guard = Some(CachedToken {
+            access_token: response.access_token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(response.expires_in),
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn request_token<T: Serialize + ?Sized>(
+    http: &Client,
+    token_url: &str,
+    form: &T,
+) -> Result<TokenResponse, Error> {
+    let response = http.post(token_url).form(form).send().await?;
+
+    if response.status().as_u16() >= 400 {
+        let err_response: TokenError = response.json().await?;
+        Err(err_response.into())
+    } else {
+        let token: TokenResponse = response.json().await?;
+        Ok(token)
+    }
+}
+
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+/// Contents of service account JSON key file, downloaded from Google Cloud console.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default = "default_token_uri")]
+    pub token_uri: String,
+}
+
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+fn default_token_uri() -> String {
+    GOOGLE_TOKEN_URL.to_owned()
+}
+
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+#[derive(Debug, Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+/// Server-to-server credential. Signs JWT assertion with the service account key
+/// and exchanges it for an access token.
+///
+/// The spreadsheet must be shared with the service account email.
+#[derive(Debug)]
+pub struct ServiceAccount {
+    key: ServiceAccountKey,
+    scopes: Vec<String>,
+    cache: TokenCache,
+}
+
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+impl ServiceAccount {
+    pub fn new(key: ServiceAccountKey) -> Self {
+        ServiceAccount {
+            key,
+            scopes: vec![SPREADSHEETS_SCOPE.to_owned()],
+            cache: TokenCache::default(),
+        }
+    }
+
+    /// Reads service account JSON key file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let key: ServiceAccountKey = serde_json::from_str(json)?;
+        Ok(Self::new(key))
+    }
+
+    /// Replaces default [`SPREADSHEETS_SCOPE`].
+    #[must_use]
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    pub fn client_email(&self) -> &str {
+        &self.key.client_email
+    }
+
+    fn assertion(&self) -> Result<String, Error> {
+        let iat = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let scope = self.scopes.join(" ");
+        let claims = Claims {
+            iss: &self.key.client_email,
+            scope: &scope,
+            aud: &self.key.token_uri,
+            iat,
+            exp: iat + ASSERTION_LIFETIME_SECS,
+        };
+        let key = EncodingKey::from_rsa_pem(self.key.private_key.as_bytes())?;
+        Ok(jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &key,
+        )?)
+    }
+}
+
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+impl CredentialProvider for ServiceAccount {
+    async fn credential(&self, http: &Client) -> Result<Credential, Error> {
+        if let Some(access_token) = self.cache.get() {
+            return Ok(Credential::AccessToken(access_token));
+        }
+
+        let assertion = self.assertion()?;
+        let token = request_token(
+            http,
+            &self.key.token_uri,
+            &[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ],
+        )
+        .await?;
+        self.cache.set(&token);
+
+        Ok(Credential::AccessToken(token.access_token))
+    }
+}
+
+/// OAuth credential of a user, who granted offline access once.
+/// Exchanges long-lived refresh token for short-lived access tokens.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct RefreshToken {
+    client_id: String,
+    client_secret: Option<String>,
+    refresh_token: String,
+    token_url: String,
+    cache: TokenCache,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RefreshToken {
+    pub fn new(client_id: String, client_secret: Option<String>, refresh_token: String) -> Self {
+        RefreshToken {
+            client_id,
+            client_secret,
+            refresh_token,
+            token_url: GOOGLE_TOKEN_URL.to_owned(),
+            cache: TokenCache::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_token_url(mut self, token_url: String) -> Self {
+        self.token_url = token_url;
+        self
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CredentialProvider for RefreshToken {
+    async fn credential(&self, http: &Client) -> Result<Credential, Error> {
+        if let Some(access_token) = self.cache.get() {
+            return Ok(Credential::AccessToken(access_token));
+        }
+
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("client_id", self.client_id.as_str()),
+            ("refresh_token", self.refresh_token.as_str()),
+        ];
+        if let Some(client_secret) = &self.client_secret {
+            form.push(("client_secret", client_secret.as_str()));
+        }
+        let token = request_token(http, &self.token_url, &form).await?;
+        self.cache.set(&token);
+
+        Ok(Credential::AccessToken(token.access_token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn token_cache_respects_expiry() {
+        let cache = TokenCache::default();
+        assert_eq!(cache.get(), None);
+
+        cache.set(&TokenResponse {
+            access_token: String::from("fresh"),
+            expires_in: 3599,
+        });
+        assert_eq!(cache.get().as_deref(), Some("fresh"));
+
+        cache.set(&TokenResponse {
+            access_token: String::from("almost expired"),
+            expires_in: 10,
+        });
+        assert_eq!(cache.get(), None);
+    }
+
+    #[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+    #[test]
+    fn service_account_key_default_token_uri() {
+        let account = ServiceAccount::from_json(
+            r#"{"client_email": "bot@project.iam.gserviceaccount.com", "private_key": "key"}"#,
+        )
+        .unwrap();
+        assert_eq!(account.key.token_uri, GOOGLE_TOKEN_URL);
+        assert_eq!(
+            account.client_email(),
+            "bot@project.iam.gserviceaccount.com"
+        );
+    }
+}
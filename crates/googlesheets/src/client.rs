@@ -0,0 +1,396 @@
+//! Sheets API client with configurable endpoint.
+
+use crate::{
+    auth::CredentialProvider,
+    error::Error,
+    range::{encode, A1Range, ToA1},
+    retry::{self, send, send_non_idempotent, RetryPolicy},
+    sheet::{
+        AddSheet, AddSheetProperties, BatchResponse, ClearValuesResponse, ReadBatchResponse,
+        SheetId, SheetUrl, SpreadsheetSheets, UpdateCells, ValueRange, ValueRenderOption,
+        WriteValuesResponse,
+    },
+};
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::{fmt::Debug, sync::LazyLock};
+
+pub const DEFAULT_BASE_URL: &str = "https://sheets.googleapis.com";
+
+static DEFAULT_CLIENT: LazyLock<SheetsClient> = LazyLock::new(SheetsClient::default);
+
+/// Client for Sheets API v4. Free functions of [`crate::sheet`] use [`SheetsClient::global`],
+/// create own client to send requests somewhere else, for example to [`crate::fake::FakeSheets`].
+#[derive(Debug, Clone)]
+pub struct SheetsClient {
+    base_url: String,
+    http: Client,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl Default for SheetsClient {
+    fn default() -> Self {
+        SheetsClient::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl SheetsClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        SheetsClient {
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            http: Client::builder()
+                .build()
+                .expect("Failed to create reqwest client"),
+            retry_policy: None,
+        }
+    }
+
+    /// Client with [`DEFAULT_BASE_URL`], shared by free functions.
+    pub fn global() -> &'static SheetsClient {
+        &DEFAULT_CLIENT
+    }
+
+    /// Overrides global policy from [`retry::set_retry_policy`] for this client.
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy.unwrap_or_else(retry::retry_policy)
+    }
+
+    fn spreadsheet_url(&self, spreadsheet_id: &str) -> String {
+        format!("{}/v4/spreadsheets/{spreadsheet_id}", self.base_url)
+    }
+
+    pub async fn read_batch<R: ToA1>(
+        &self,
+        spreadsheet_id: &str,
+        ranges: &[R],
+        credential: impl CredentialProvider,
+    ) -> Result<ReadBatchResponse, Error> {
+        let formatted_ranges = ranges
+            .iter()
+            .map(|range| format!("ranges={}", encode(&range.to_a1())))
+            .collect::<Vec<String>>()
+            .join("&");
+
+        let url = format!(
+            "{}/values:batchGet?{formatted_ranges}",
+            self.spreadsheet_url(spreadsheet_id)
+        );
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(self.http.get(url));
+        let response = send(request, self.retry_policy()).await?;
+
+        let value: ReadBatchResponse = response.json().await?;
+        Ok(value)
+    }
+
+    pub async fn read(
+        &self,
+        spreadsheet_id: &str,
+        range: impl ToA1,
+        credential: impl CredentialProvider,
+    ) -> Result<ValueRange, Error> {
+        self.read_rendered(
+            spreadsheet_id,
+            range,
+            ValueRenderOption::FormattedValue,
+            credential,
+        )
+        .await
+    }
+
+    /// Reads values rendered with the given option, for example
+    /// [`ValueRenderOption::UnformattedValue`] to get numbers instead of formatted strings.
+    pub async fn read_rendered(
+        &self,
+        spreadsheet_id: &str,
+        range: impl ToA1,
+        render: ValueRenderOption,
+        credential: impl CredentialProvider,
+    ) -> Result<ValueRange, Error> {
+        let url = format!(
+            "{}/values/{}",
+            self.spreadsheet_url(spreadsheet_id),
+            encode(&range.to_a1())
+        );
+        let request = credential.credential(&self.http).await?.authorize(
+            self.http
+                .get(url)
+                .query(&[("valueRenderOption", render.as_str())]),
+        );
+        let response = send(request, self.retry_policy()).await?;
+
+        let value_range: ValueRange = response.json().await?;
+        Ok(value_range)
+    }
+
+    /// spreadsheets.get. With `include_grid_data` the response holds cell formats
+    /// and text format runs of requested ranges.
+    pub async fn get_spreadsheet<T: DeserializeOwned, R: ToA1>(
+        &self,
+        spreadsheet_id: &str,
+        ranges: &[R],
+        include_grid_data: bool,
+        credential: impl CredentialProvider,
+    ) -> Result<T, Error> {
+        let mut query: Vec<(&str, String)> = ranges
+            .iter()
+            .map(|range| ("ranges", range.to_a1()))
+            .collect();
+        if include_grid_data {
+            query.push(("includeGridData", String::from("true")));
+        }
+
+        let request = credential.credential(&self.http).await?.authorize(
+            self.http
+                .get(self.spreadsheet_url(spreadsheet_id))
+                .query(&query),
+        );
+        let response = send(request, self.retry_policy()).await?;
+
+        let spreadsheet: T = response.json().await?;
+        Ok(spreadsheet)
+    }
+
+    #[tracing::instrument(skip(self, data, credential))]
+    pub async fn batch_update(
+        &self,
+        spreadsheet_id: &str,
+        data: Vec<ValueRange>,
+        credential: impl CredentialProvider,
+    ) -> Result<Value, Error> {
+        let request = self.http.post(format!(
+            "{}/values:batchUpdate",
+            self.spreadsheet_url(spreadsheet_id)
+        ));
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(request)
+            .body(
+                json!({
+                    "valueInputOption": "RAW",
+                    "data": data
+                })
+                .to_string(),
+            );
+        let response = send(request, self.retry_policy()).await?;
+
+        let write_values: Value = response.json().await?;
+        Ok(write_values)
+    }
+
+    /// spreadsheets.batchUpdate with `updateCells` requests.
+    #[tracing::instrument(skip(self, requests, credential))]
+    pub async fn update_cells(
+        &self,
+        spreadsheet_id: &str,
+        requests: Vec<UpdateCells>,
+        credential: impl CredentialProvider,
+    ) -> Result<(), Error> {
+        let requests: Vec<Value> = requests
+            .into_iter()
+            .map(|update_cells| json!({ "updateCells": update_cells }))
+            .collect();
+        let request = self.http.post(format!(
+            "{}:batchUpdate",
+            self.spreadsheet_url(spreadsheet_id)
+        ));
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(request)
+            .body(json!({ "requests": requests }).to_string());
+        send(request, self.retry_policy()).await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self, values, credential))]
+    pub async fn add_sheet_with_values<T: Serialize + Debug>(
+        &self,
+        spreadsheet_id: &str,
+        title: &str,
+        values: Vec<Vec<Value>>,
+        credential: impl CredentialProvider,
+    ) -> Result<SheetUrl, Error> {
+        let add_sheet_data = self.add_sheet(spreadsheet_id, title, &credential).await?;
+        let _ = self
+            .write_values_into_sheet(spreadsheet_id, title, &credential, values)
+            .await?;
+
+        Ok(SheetUrl::create(
+            spreadsheet_id,
+            add_sheet_data.properties.sheet_id,
+        ))
+    }
+
+    pub async fn write_values_into_sheet(
+        &self,
+        spreadsheet_id: &str,
+        title: &str,
+        credential: impl CredentialProvider,
+        values: Vec<Vec<Value>>,
+    ) -> Result<WriteValuesResponse, Error> {
+        let range = A1Range::sheet(title);
+        let url = format!(
+            "{}/values/{}?valueInputOption=RAW",
+            self.spreadsheet_url(spreadsheet_id),
+            range.url_encoded()
+        );
+
+        let body = serde_json::to_string(&json!({
+          "range": range,
+          "majorDimension": "ROWS",
+          "values": values
+        }))?;
+
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(self.http.put(url))
+            .body(body);
+        let response = send(request, self.retry_policy()).await?;
+
+        let write_values: WriteValuesResponse = response.json().await?;
+        Ok(write_values)
+    }
+
+    pub async fn add_sheet(
+        &self,
+        spreadsheet_id: &str,
+        title: &str,
+        credential: impl CredentialProvider,
+    ) -> Result<AddSheet, Error> {
+        let request = self.http.post(format!(
+            "{}:batchUpdate",
+            self.spreadsheet_url(spreadsheet_id)
+        ));
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(request)
+            .body(
+                json!({
+                   "requests":[
+                      {
+                         "addSheet":{
+                            "properties":{
+                               "title": title
+                            }
+                         }
+                      }
+                   ]
+                })
+                .to_string(),
+            );
+        // A retried addSheet, that already went through, fails with "sheet already exists".
+        let response = send_non_idempotent(request, self.retry_policy()).await?;
+
+        let response: BatchResponse = response.json().await?;
+        let add_sheet = response.replies[0].add_sheet.clone();
+
+        Ok(add_sheet)
+    }
+
+    /// Lists properties of every sheet(tab) in the spreadsheet.
+    pub async fn list_sheets(
+        &self,
+        spreadsheet_id: &str,
+        credential: impl CredentialProvider,
+    ) -> Result<Vec<AddSheetProperties>, Error> {
+        let request = self.http.get(format!(
+            "{}?fields=sheets.properties",
+            self.spreadsheet_url(spreadsheet_id)
+        ));
+        let request = credential.credential(&self.http).await?.authorize(request);
+        let response = send(request, self.retry_policy()).await?;
+
+        let spreadsheet: SpreadsheetSheets = response.json().await?;
+        Ok(spreadsheet
+            .sheets
+            .into_iter()
+            .map(|sheet| sheet.properties)
+            .collect())
+    }
+
+    /// Finds the id of the sheet(tab) with given title.
+    pub async fn sheet_id_by_title(
+        &self,
+        spreadsheet_id: &str,
+        title: &str,
+        credential: impl CredentialProvider,
+    ) -> Result<Option<SheetId>, Error> {
+        let sheets = self.list_sheets(spreadsheet_id, credential).await?;
+        Ok(sheets
+            .into_iter()
+            .find(|properties| properties.title == title)
+            .map(|properties| properties.sheet_id))
+    }
+
+    /// Clears values from the range, keeping formatting.
+    pub async fn clear_range(
+        &self,
+        spreadsheet_id: &str,
+        range: impl ToA1,
+        credential: impl CredentialProvider,
+    ) -> Result<ClearValuesResponse, Error> {
+        let request = self.http.post(format!(
+            "{}/values/{}:clear",
+            self.spreadsheet_url(spreadsheet_id),
+            encode(&range.to_a1())
+        ));
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(request)
+            .body("{}");
+        let response = send(request, self.retry_policy()).await?;
+
+        let cleared: ClearValuesResponse = response.json().await?;
+        Ok(cleared)
+    }
+
+    pub async fn delete_sheet(
+        &self,
+        spreadsheet_id: &str,
+        sheet_id: &SheetId,
+        credential: impl CredentialProvider,
+    ) -> Result<(), Error> {
+        let request = self.http.post(format!(
+            "{}:batchUpdate",
+            self.spreadsheet_url(spreadsheet_id)
+        ));
+        let request = credential
+            .credential(&self.http)
+            .await?
+            .authorize(request)
+            .body(
+                json!({
+                   "requests":[
+                      {
+                         "deleteSheet":{
+                            "sheetId": sheet_id
+                         }
+                      }
+                   ]
+                })
+                .to_string(),
+            );
+        send_non_idempotent(request, self.retry_policy()).await?;
+
+        Ok(())
+    }
+}
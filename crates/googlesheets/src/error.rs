@@ -1,3 +1,4 @@
+use crate::auth::TokenError;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -13,6 +14,33 @@ pub struct GoogleErrorResponse {
     pub error: GoogleError,
 }
 
+/// Broad category of a failed request, to decide what to do about it.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// Rate limit or quota exceeded. Repeat later.
+    Quota,
+    /// Missing, expired or insufficient credential.
+    Auth,
+    /// Spreadsheet, sheet or range does not exist.
+    NotFound,
+    /// Google servers or network are unavailable at the moment.
+    Unavailable,
+    Other,
+}
+
+impl GoogleError {
+    pub fn kind(&self) -> ErrorKind {
+        match (self.code, self.status.as_str()) {
+            (429, _) | (_, "RESOURCE_EXHAUSTED") => ErrorKind::Quota,
+            (401 | 403, _) | (_, "UNAUTHENTICATED" | "PERMISSION_DENIED") => ErrorKind::Auth,
+            (404, _) | (_, "NOT_FOUND") => ErrorKind::NotFound,
+            (500..=599, _) | (_, "UNAVAILABLE") => ErrorKind::Unavailable,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
 impl Display for GoogleError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.message)
@@ -24,6 +52,25 @@ pub enum Error {
     ReqwestError(reqwest::Error),
     GoogleError(GoogleError),
     SerdeError(serde_json::Error),
+    IoError(std::io::Error),
+    #[cfg(feature = "service-account")]
+    JwtError(jsonwebtoken::errors::Error),
+    TokenError(TokenError),
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::GoogleError(err) => err.kind(),
+            Error::ReqwestError(err) if err.is_timeout() || err.is_connect() => {
+                ErrorKind::Unavailable
+            }
+            Error::TokenError(_) => ErrorKind::Auth,
+            #[cfg(feature = "service-account")]
+            Error::JwtError(_) => ErrorKind::Auth,
+            _ => ErrorKind::Other,
+        }
+    }
 }
 
 impl Display for Error {
@@ -32,6 +79,10 @@ impl Display for Error {
             Error::ReqwestError(err) => err.fmt(f),
             Error::GoogleError(err) => err.fmt(f),
             Error::SerdeError(err) => err.fmt(f),
+            Error::IoError(err) => err.fmt(f),
+            #[cfg(feature = "service-account")]
+            Error::JwtError(err) => err.fmt(f),
+            Error::TokenError(err) => err.fmt(f),
         }
     }
 }
@@ -53,3 +104,42 @@ impl From<serde_json::Error> for Error {
         Error::SerdeError(value)
     }
 }
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::IoError(value)
+    }
+}
+
+#[cfg(feature = "service-account")]
+impl From<jsonwebtoken::errors::Error> for Error {
+    fn from(value: jsonwebtoken::errors::Error) -> Self {
+        Error::JwtError(value)
+    }
+}
+
+impl From<TokenError> for Error {
+    fn from(value: TokenError) -> Self {
+        Error::TokenError(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn google_error_kind() {
+        let error = |code: u32, status: &str| GoogleError {
+            code,
+            message: String::new(),
+            status: status.to_owned(),
+        };
+        assert_eq!(error(429, "RESOURCE_EXHAUSTED").kind(), ErrorKind::Quota);
+        assert_eq!(error(403, "PERMISSION_DENIED").kind(), ErrorKind::Auth);
+        assert_eq!(error(401, "UNAUTHENTICATED").kind(), ErrorKind::Auth);
+        assert_eq!(error(404, "NOT_FOUND").kind(), ErrorKind::NotFound);
+        assert_eq!(error(503, "UNAVAILABLE").kind(), ErrorKind::Unavailable);
+        assert_eq!(error(400, "INVALID_ARGUMENT").kind(), ErrorKind::Other);
+    }
+}
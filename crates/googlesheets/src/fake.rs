@@ -0,0 +1,938 @@
+//! In-process fake of Sheets API for tests, that do not want to touch network.
+//!
+//! Supports values get, batchGet, update(put), batchUpdate and clear,
+//! spreadsheets get (with `includeGridData`) and spreadsheets batchUpdate with addSheet, deleteSheet
+//! and updateCells.
+//! State lives in memory of the process and can be inspected and seeded through [`FakeSheets`].
+//!
+//! ```
+//! # use googlesheets::{fake::FakeSheets, sheet::Credential};
+//! # use serde_json::json;
+//! # #[tokio::main]
+//! # async fn main() {
+//! let fake = FakeSheets::start().await.unwrap();
+//! fake.set_values("spreadsheet", "Sheet1", vec![vec![json!("name"), json!("amount")]]);
+//!
+//! let client = fake.client();
+//! let credential = Credential::ApiKey(String::from("any"));
+//! let value_range = client.read("spreadsheet", "Sheet1!A1:B", &credential).await.unwrap();
+//! assert_eq!(value_range.values, vec![vec![json!("name"), json!("amount")]]);
+//! # }
+//! ```
+
+use crate::{client::SheetsClient, range::A1Range, retry::RetryPolicy};
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// One cell of a fake sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FakeCell {
+    pub value: Value,
+    /// `effectiveFormat` of the cell, plain text format is used if empty.
+    pub effective_format: Option<Value>,
+    /// `textFormatRuns` of the cell.
+    pub text_format_runs: Option<Value>,
+}
+
+impl FakeCell {
+    pub fn new(value: Value) -> Self {
+        FakeCell {
+            value,
+            ..Default::default()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match &self.value {
+            Value::Null => true,
+            Value::String(s) => s.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn grid_data(&self) -> Value {
+        let mut cell = Map::new();
+        if !self.is_empty() {
+            cell.insert(
+                String::from("formattedValue"),
+                Value::String(formatted(&self.value)),
+            );
+        }
+        cell.insert(
+            String::from("effectiveFormat"),
+            self.effective_format.clone().unwrap_or_else(plain_format),
+        );
+        if let Some(runs) = &self.text_format_runs {
+            cell.insert(String::from("textFormatRuns"), runs.clone());
+        }
+        Value::Object(cell)
+    }
+}
+
+fn plain_format() -> Value {
+    json!({
+        "backgroundColor": {"red": 1, "green": 1, "blue": 1},
+        "textFormat": {
+            "foregroundColor": {},
+            "italic": false,
+            "strikethrough": false
+        }
+    })
+}
+
+#[derive(Debug, Clone)]
+struct FakeSheet {
+    sheet_id: u32,
+    title: String,
+    cells: Vec<Vec<FakeCell>>,
+}
+
+impl FakeSheet {
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn width(&self) -> usize {
+        self.cells.iter().map(Vec::len).max().unwrap_or_default()
+    }
+
+    fn cell_mut(&mut self, row: usize, column: usize) -> &mut FakeCell {
+        if self.cells.len() <= row {
+            self.cells.resize(row + 1, vec![]);
+        }
+        let cells = &mut self.cells[row];
+        if cells.len() <= column {
+            cells.resize(column + 1, FakeCell::default());
+        }
+        &mut cells[column]
+    }
+
+    fn properties(&self, index: usize) -> Value {
+        json!({
+            "sheetId": self.sheet_id,
+            "title": self.title,
+            "index": index,
+            "sheetType": "GRID",
+            "gridProperties": {
+                "rowCount": self.height().max(1000),
+                "columnCount": self.width().max(26)
+            }
+        })
+    }
+
+    /// Resolves open ends of the range against sheet contents. End bounds are exclusive.
+    fn bounds(&self, range: &GridRange) -> (usize, usize, usize, usize) {
+        let end_row = range
+            .end_row
+            .unwrap_or_else(|| self.height())
+            .max(range.start_row);
+        let end_column = range
+            .end_column
+            .unwrap_or_else(|| self.width())
+            .max(range.start_column);
+        (range.start_row, end_row, range.start_column, end_column)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FakeSpreadsheet {
+    sheets: Vec<FakeSheet>,
+    next_sheet_id: u32,
+}
+
+impl FakeSpreadsheet {
+    fn add_sheet(&mut self, title: &str) -> &mut FakeSheet {
+        self.sheets.push(FakeSheet {
+            sheet_id: self.next_sheet_id,
+            title: title.to_owned(),
+            cells: vec![],
+        });
+        self.next_sheet_id += 1;
+        self.sheets.last_mut().unwrap()
+    }
+
+    fn sheet_mut(&mut self, title: &str) -> Option<&mut FakeSheet> {
+        self.sheets.iter_mut().find(|sheet| sheet.title == title)
+    }
+
+    fn sheet_or_add(&mut self, title: &str) -> &mut FakeSheet {
+        match self.sheets.iter().position(|sheet| sheet.title == title) {
+            Some(index) => &mut self.sheets[index],
+            None => self.add_sheet(title),
+        }
+    }
+}
+
+type Spreadsheets = Arc<Mutex<HashMap<String, FakeSpreadsheet>>>;
+
+/// Running fake Sheets server. Stops, when dropped.
+#[derive(Debug)]
+pub struct FakeSheets {
+    addr: SocketAddr,
+    spreadsheets: Spreadsheets,
+    server: JoinHandle<()>,
+}
+
+impl FakeSheets {
+    /// Starts the server on a random local port.
+    pub async fn start() -> std::io::Result<Self> {
+        let spreadsheets = Spreadsheets::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let app = Router::new()
+            .fallback(handle)
+            .with_state(spreadsheets.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        Ok(FakeSheets {
+            addr,
+            spreadsheets,
+            server,
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Client pointed to this server. Fake never asks to retry, so retries are disabled.
+    pub fn client(&self) -> SheetsClient {
+        SheetsClient::new(self.base_url()).with_retry_policy(RetryPolicy::NONE)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, FakeSpreadsheet>> {
+        lock(&self.spreadsheets)
+    }
+
+    /// Creates empty spreadsheet with given sheets. Existing spreadsheet is replaced.
+    pub fn create_spreadsheet(&self, spreadsheet_id: &str, titles: &[&str]) {
+        let mut spreadsheet = FakeSpreadsheet::default();
+        for title in titles {
+            spreadsheet.add_sheet(title);
+        }
+        self.lock().insert(spreadsheet_id.to_owned(), spreadsheet);
+    }
+
+    /// Replaces contents of the sheet, starting from A1. Creates spreadsheet and sheet, if needed.
+    pub fn set_values(&self, spreadsheet_id: &str, title: &str, values: Vec<Vec<Value>>) {
+        let mut guard = self.lock();
+        let sheet = guard
+            .entry(spreadsheet_id.to_owned())
+            .or_default()
+            .sheet_or_add(title);
+        sheet.cells = values
+            .into_iter()
+            .map(|row| row.into_iter().map(FakeCell::new).collect())
+            .collect();
+    }
+
+    /// Sets one cell, row and column are zero-based. Creates spreadsheet and sheet, if needed.
+    pub fn set_cell(
+        &self,
+        spreadsheet_id: &str,
+        title: &str,
+        row: usize,
+        column: usize,
+        cell: FakeCell,
+    ) {
+        let mut guard = self.lock();
+        let sheet = guard
+            .entry(spreadsheet_id.to_owned())
+            .or_default()
+            .sheet_or_add(title);
+        *sheet.cell_mut(row, column) = cell;
+    }
+
+    /// Current values of the sheet as they were written, trailing empty cells are trimmed.
+    pub fn values(&self, spreadsheet_id: &str, title: &str) -> Option<Vec<Vec<Value>>> {
+        let guard = self.lock();
+        let sheet = guard
+            .get(spreadsheet_id)?
+            .sheets
+            .iter()
+            .find(|sheet| sheet.title == title)?;
+        let range = GridRange::default();
+        Some(read_values(sheet, &range, false))
+    }
+
+    pub fn sheet_titles(&self, spreadsheet_id: &str) -> Vec<String> {
+        self.lock()
+            .get(spreadsheet_id)
+            .map(|spreadsheet| {
+                spreadsheet
+                    .sheets
+                    .iter()
+                    .map(|sheet| sheet.title.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for FakeSheets {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn lock(spreadsheets: &Spreadsheets) -> MutexGuard<'_, HashMap<String, FakeSpreadsheet>> {
+    spreadsheets
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Zero-based rectangle of a sheet. `None` ends are open.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GridRange {
+    start_row: usize,
+    end_row: Option<usize>,
+    start_column: usize,
+    end_column: Option<usize>,
+}
+
+/// Splits A1 notation into sheet title and grid range. Range without title refers to the first sheet.
+fn parse_a1(range: &str) -> Option<(Option<String>, GridRange)> {
+    let range: A1Range = range.parse().ok()?;
+    let start = range.start.unwrap_or_default();
+    // Single cell is a range, that ends where it starts
+    let end = range.end.or(range.start).unwrap_or_default();
+
+    let grid = GridRange {
+        start_row: start.row.map_or(0, |row| row as usize - 1),
+        end_row: end.row.map(|row| row as usize),
+        start_column: start.column.map_or(0, |column| column.index() as usize),
+        end_column: end.column.map(|column| column.index() as usize + 1),
+    };
+
+    Some((range.sheet, grid))
+}
+
+fn formatted(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string().to_uppercase(),
+        other => other.to_string(),
+    }
+}
+
+/// Reads values like the API does: trailing empty cells and rows are omitted.
+fn read_values(sheet: &FakeSheet, range: &GridRange, render_formatted: bool) -> Vec<Vec<Value>> {
+    let (start_row, end_row, start_column, end_column) = sheet.bounds(range);
+    let mut values: Vec<Vec<Value>> = (start_row..end_row.min(sheet.height()))
+        .map(|row| {
+            let cells = &sheet.cells[row];
+            let mut row_values: Vec<Value> = (start_column..end_column.min(cells.len()))
+                .map(|column| {
+                    let cell = &cells[column];
+                    match (render_formatted, cell.is_empty()) {
+                        (_, true) => Value::String(String::new()),
+                        (true, false) => Value::String(formatted(&cell.value)),
+                        (false, false) => cell.value.clone(),
+                    }
+                })
+                .collect();
+            while row_values.last().is_some_and(|v| v == "") {
+                row_values.pop();
+            }
+            row_values
+        })
+        .collect();
+    while values.last().is_some_and(Vec::is_empty) {
+        values.pop();
+    }
+    values
+}
+
+fn write_values(sheet: &mut FakeSheet, range: &GridRange, values: &[Vec<Value>], columns: bool) {
+    for (i, line) in values.iter().enumerate() {
+        for (j, value) in line.iter().enumerate() {
+            // null means "leave the cell as is"
+            if value.is_null() {
+                continue;
+            }
+            let (row, column) = match columns {
+                true => (range.start_row + j, range.start_column + i),
+                false => (range.start_row + i, range.start_column + j),
+            };
+            sheet.cell_mut(row, column).value = value.clone();
+        }
+    }
+}
+
+struct FakeError(StatusCode, String);
+
+impl IntoResponse for FakeError {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            StatusCode::NOT_FOUND => "NOT_FOUND",
+            _ => "INVALID_ARGUMENT",
+        };
+        let body = json!({
+            "error": {
+                "code": self.0.as_u16(),
+                "message": self.1,
+                "status": status
+            }
+        });
+        (self.0, Json(body)).into_response()
+    }
+}
+
+fn not_found() -> FakeError {
+    FakeError(
+        StatusCode::NOT_FOUND,
+        String::from("Requested entity was not found."),
+    )
+}
+
+fn bad_request(message: impl Into<String>) -> FakeError {
+    FakeError(StatusCode::BAD_REQUEST, message.into())
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match u8::from_str_radix(&s[i + 1..i + 3], 16) {
+                Ok(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                    continue;
+                }
+                Err(_) => decoded.push(b'%'),
+            },
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn query_pairs(uri: &Uri) -> Vec<(String, String)> {
+    uri.query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+async fn handle(
+    State(spreadsheets): State<Spreadsheets>,
+    method: Method,
+    uri: Uri,
+    body: Bytes,
+) -> Result<Json<Value>, FakeError> {
+    let path = uri
+        .path()
+        .strip_prefix("/v4/spreadsheets/")
+        .ok_or_else(not_found)?;
+    let query = query_pairs(&uri);
+    let body: Value = match body.is_empty() {
+        true => Value::Null,
+        false => serde_json::from_slice(&body).map_err(|err| bad_request(err.to_string()))?,
+    };
+
+    let (spreadsheet_id, rest) = match path.split_once('/') {
+        Some((id, rest)) => (id, Some(rest)),
+        None => (path, None),
+    };
+    let (spreadsheet_id, batch_update) = match spreadsheet_id.strip_suffix(":batchUpdate") {
+        Some(id) => (percent_decode(id), true),
+        None => (percent_decode(spreadsheet_id), false),
+    };
+
+    let mut guard = lock(&spreadsheets);
+    let spreadsheet = guard.get_mut(&spreadsheet_id).ok_or_else(not_found)?;
+
+    let response = match (method, rest) {
+        (Method::GET, None) => get_spreadsheet(spreadsheet, &spreadsheet_id, &query)?,
+        (Method::POST, None) if batch_update => {
+            update_spreadsheet(spreadsheet, &spreadsheet_id, &body)?
+        }
+        (Method::GET, Some("values:batchGet")) => {
+            let value_ranges = query
+                .iter()
+                .filter(|(key, _)| key == "ranges")
+                .map(|(_, range)| get_values(spreadsheet, range, &query))
+                .collect::<Result<Vec<Value>, FakeError>>()?;
+            json!({"spreadsheetId": spreadsheet_id, "valueRanges": value_ranges})
+        }
+        (Method::POST, Some("values:batchUpdate")) => {
+            let data = body["data"].as_array().cloned().unwrap_or_default();
+            let mut responses = vec![];
+            for value_range in &data {
+                responses.push(put_values(spreadsheet, &spreadsheet_id, value_range)?);
+            }
+            json!({
+                "spreadsheetId": spreadsheet_id,
+                "totalUpdatedSheets": responses.len(),
+                "responses": responses
+            })
+        }
+        (method, Some(values)) if values.starts_with("values/") => {
+            let range = percent_decode(&values["values/".len()..]);
+            match (method, range.strip_suffix(":clear")) {
+                (Method::POST, Some(range)) => {
+                    clear_values(spreadsheet, range)?;
+                    json!({"spreadsheetId": spreadsheet_id, "clearedRange": range})
+                }
+                (Method::GET, None) => get_values(spreadsheet, &range, &query)?,
+                (Method::PUT, None) => {
+                    let mut value_range = body.clone();
+                    value_range["range"] = Value::String(range);
+                    put_values(spreadsheet, &spreadsheet_id, &value_range)?
+                }
+                _ => return Err(not_found()),
+            }
+        }
+        _ => return Err(not_found()),
+    };
+
+    Ok(Json(response))
+}
+
+fn sheet_for_range<'a>(
+    spreadsheet: &'a mut FakeSpreadsheet,
+    range: &str,
+) -> Result<(&'a mut FakeSheet, GridRange), FakeError> {
+    let unable = || bad_request(format!("Unable to parse range: {range}"));
+    let (title, grid) = parse_a1(range).ok_or_else(unable)?;
+    let sheet = match title {
+        Some(title) => spreadsheet.sheet_mut(&title),
+        None => spreadsheet.sheets.first_mut(),
+    }
+    .ok_or_else(unable)?;
+    Ok((sheet, grid))
+}
+
+fn get_values(
+    spreadsheet: &mut FakeSpreadsheet,
+    range: &str,
+    query: &[(String, String)],
+) -> Result<Value, FakeError> {
+    let render_formatted = !query
+        .iter()
+        .any(|(key, value)| key == "valueRenderOption" && value != "FORMATTED_VALUE");
+    let (sheet, grid) = sheet_for_range(spreadsheet, range)?;
+    let values = read_values(sheet, &grid, render_formatted);
+
+    let mut value_range = json!({"range": range, "majorDimension": "ROWS"});
+    if !values.is_empty() {
+        value_range["values"] = json!(values);
+    }
+    Ok(value_range)
+}
+
+fn put_values(
+    spreadsheet: &mut FakeSpreadsheet,
+    spreadsheet_id: &str,
+    value_range: &Value,
+) -> Result<Value, FakeError> {
+    let range = value_range["range"]
+        .as_str()
+        .ok_or_else(|| bad_request("Missing range"))?;
+    let values: Vec<Vec<Value>> = serde_json::from_value(value_range["values"].clone())
+        .map_err(|err| bad_request(err.to_string()))?;
+    let columns = value_range["majorDimension"] == "COLUMNS";
+
+    let (sheet, grid) = sheet_for_range(spreadsheet, range)?;
+    write_values(sheet, &grid, &values, columns);
+
+    let updated_cells: usize = values.iter().map(Vec::len).sum();
+    Ok(json!({
+        "spreadsheetId": spreadsheet_id,
+        "updatedRange": range,
+        "updatedRows": values.len(),
+        "updatedColumns": values.iter().map(Vec::len).max().unwrap_or_default(),
+        "updatedCells": updated_cells
+    }))
+}
+
+fn clear_values(spreadsheet: &mut FakeSpreadsheet, range: &str) -> Result<(), FakeError> {
+    let (sheet, grid) = sheet_for_range(spreadsheet, range)?;
+    let (start_row, end_row, start_column, end_column) = sheet.bounds(&grid);
+    for row in sheet.cells.iter_mut().take(end_row).skip(start_row) {
+        for cell in row.iter_mut().take(end_column).skip(start_column) {
+            cell.value = Value::Null;
+        }
+    }
+    Ok(())
+}
+
+fn get_spreadsheet(
+    spreadsheet: &mut FakeSpreadsheet,
+    spreadsheet_id: &str,
+    query: &[(String, String)],
+) -> Result<Value, FakeError> {
+    let include_grid_data = query
+        .iter()
+        .any(|(key, value)| key == "includeGridData" && value == "true");
+    let ranges: Vec<&str> = query
+        .iter()
+        .filter(|(key, _)| key == "ranges")
+        .map(|(_, range)| range.as_str())
+        .collect();
+
+    let mut sheets: Vec<Value> = vec![];
+    if ranges.is_empty() {
+        for (index, sheet) in spreadsheet.sheets.iter().enumerate() {
+            let mut value = json!({"properties": sheet.properties(index)});
+            if include_grid_data {
+                value["data"] = json!([grid_data(sheet, &GridRange::default())]);
+            }
+            sheets.push(value);
+        }
+    } else {
+        for range in ranges {
+            let (_, grid) = sheet_for_range(spreadsheet, range)?;
+            let index = spreadsheet_index(spreadsheet, range);
+            let sheet = &spreadsheet.sheets[index];
+            let mut value = json!({"properties": sheet.properties(index)});
+            if include_grid_data {
+                value["data"] = json!([grid_data(sheet, &grid)]);
+            }
+            sheets.push(value);
+        }
+    }
+
+    Ok(json!({
+        "spreadsheetId": spreadsheet_id,
+        "sheets": sheets
+    }))
+}
+
+fn spreadsheet_index(spreadsheet: &FakeSpreadsheet, range: &str) -> usize {
+    let title = parse_a1(range).and_then(|(title, _)| title);
+    title
+        .and_then(|title| {
+            spreadsheet
+                .sheets
+                .iter()
+                .position(|sheet| sheet.title == title)
+        })
+        .unwrap_or_default()
+}
+
+fn grid_data(sheet: &FakeSheet, range: &GridRange) -> Value {
+    let (start_row, end_row, start_column, end_column) = sheet.bounds(range);
+    let empty = FakeCell::default();
+    let row_data: Vec<Value> = (start_row..end_row.min(sheet.height()))
+        .map(|row| {
+            let values: Vec<Value> = (start_column..end_column)
+                .map(|column| sheet.cells[row].get(column).unwrap_or(&empty).grid_data())
+                .collect();
+            json!({"values": values})
+        })
+        .collect();
+
+    json!({
+        "startRow": start_row,
+        "startColumn": start_column,
+        "rowData": row_data
+    })
+}
+
+fn update_spreadsheet(
+    spreadsheet: &mut FakeSpreadsheet,
+    spreadsheet_id: &str,
+    body: &Value,
+) -> Result<Value, FakeError> {
+    let requests = body["requests"].as_array().cloned().unwrap_or_default();
+    let mut replies = vec![];
+    for request in &requests {
+        if let Some(title) = request["addSheet"]["properties"]["title"].as_str() {
+            if spreadsheet.sheet_mut(title).is_some() {
+                return Err(bad_request(format!(
+                    "A sheet with the name \"{title}\" already exists."
+                )));
+            }
+            let index = spreadsheet.sheets.len();
+            let sheet = spreadsheet.add_sheet(title);
+            replies.push(json!({"addSheet": {"properties": sheet.properties(index)}}));
+        } else if let Some(sheet_id) = request["deleteSheet"]["sheetId"].as_u64() {
+            let index = spreadsheet
+                .sheets
+                .iter()
+                .position(|sheet| u64::from(sheet.sheet_id) == sheet_id)
+                .ok_or_else(|| bad_request(format!("No grid with id: {sheet_id}")))?;
+            spreadsheet.sheets.remove(index);
+            replies.push(json!({}));
+        } else if request["updateCells"].is_object() {
+            update_cells(spreadsheet, &request["updateCells"])?;
+            replies.push(json!({}));
+        } else {
+            return Err(bad_request(format!("Unsupported request: {request}")));
+        }
+    }
+
+    Ok(json!({
+        "spreadsheetId": spreadsheet_id,
+        "replies": replies
+    }))
+}
+
+/// Writes values, text format runs and italic of the cell format. Other formats are kept.
+fn update_cells(spreadsheet: &mut FakeSpreadsheet, update_cells: &Value) -> Result<(), FakeError> {
+    let start = &update_cells["start"];
+    let sheet_id = start["sheetId"].as_u64().unwrap_or_default();
+    let sheet = spreadsheet
+        .sheets
+        .iter_mut()
+        .find(|sheet| u64::from(sheet.sheet_id) == sheet_id)
+        .ok_or_else(|| bad_request(format!("No grid with id: {sheet_id}")))?;
+    let start_row = start["rowIndex"].as_u64().unwrap_or_default() as usize;
+    let start_column = start["columnIndex"].as_u64().unwrap_or_default() as usize;
+
+    let rows = update_cells["rows"].as_array().cloned().unwrap_or_default();
+    for (row_offset, row) in rows.iter().enumerate() {
+        let values = row["values"].as_array().cloned().unwrap_or_default();
+        for (column_offset, cell_data) in values.iter().enumerate() {
+            let cell = sheet.cell_mut(start_row + row_offset, start_column + column_offset);
+            cell.value = cell_data["userEnteredValue"]["stringValue"].clone();
+            cell.text_format_runs = cell_data
+                .get("textFormatRuns")
+                .filter(|runs| runs.as_array().is_some_and(|runs| !runs.is_empty()))
+                .cloned();
+            if let Some(italic) = cell_data["userEnteredFormat"]["textFormat"]["italic"].as_bool() {
+                let mut format = cell.effective_format.clone().unwrap_or_else(plain_format);
+                format["textFormat"]["italic"] = Value::Bool(italic);
+                cell.effective_format = Some(format);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::Credential;
+
+    fn credential() -> Credential {
+        Credential::ApiKey(String::from("fake"))
+    }
+
+    #[test]
+    fn parses_a1() {
+        let (title, grid) = parse_a1("Cards_and_Hypotheses!A3:Z").unwrap();
+        assert_eq!(title.as_deref(), Some("Cards_and_Hypotheses"));
+        assert_eq!(
+            grid,
+            GridRange {
+                start_row: 2,
+                end_row: None,
+                start_column: 0,
+                end_column: Some(26),
+            }
+        );
+
+        let (title, grid) = parse_a1("'It''s mine'!H5").unwrap();
+        assert_eq!(title.as_deref(), Some("It's mine"));
+        assert_eq!(grid.start_row, 4);
+        assert_eq!(grid.start_column, 7);
+        assert_eq!(grid.end_row, Some(5));
+
+        let (title, grid) = parse_a1("3.28!F3:F").unwrap();
+        assert_eq!(title.as_deref(), Some("3.28"));
+        assert_eq!(grid.start_column, 5);
+        assert_eq!(grid.end_column, Some(6));
+
+        assert_eq!(parse_a1("Sheet1").unwrap().0.as_deref(), Some("Sheet1"));
+    }
+
+    #[tokio::test]
+    async fn values_roundtrip() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.create_spreadsheet("id", &["science"]);
+        let client = fake.client();
+
+        client
+            .batch_update(
+                "id",
+                vec![crate::sheet::ValueRange::new(
+                    crate::sheet::Dimension::Rows,
+                    String::from("science!B2"),
+                    vec![vec![json!("name"), json!(5)]],
+                )],
+                credential(),
+            )
+            .await
+            .unwrap();
+
+        let formatted = client
+            .read("id", "science!B2:C", credential())
+            .await
+            .unwrap();
+        assert_eq!(formatted.values, vec![vec![json!("name"), json!("5")]]);
+        let unformatted = client
+            .read_rendered(
+                "id",
+                "science!B2:C",
+                crate::sheet::ValueRenderOption::UnformattedValue,
+                credential(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(unformatted.values, vec![vec![json!("name"), json!(5)]]);
+
+        let batch = client
+            .read_batch("id", &["science!B2:B", "science!C2:C"], credential())
+            .await
+            .unwrap();
+        assert_eq!(batch.value_ranges.len(), 2);
+        assert_eq!(batch.value_ranges[1].values, vec![vec![json!("5")]]);
+
+        client
+            .clear_range("id", "science!C2", credential())
+            .await
+            .unwrap();
+        assert_eq!(
+            fake.values("id", "science").unwrap(),
+            vec![vec![], vec![json!(""), json!("name")]]
+        );
+    }
+
+    #[tokio::test]
+    async fn sheets_metadata() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.create_spreadsheet("id", &["Sheet1"]);
+        let client = fake.client();
+
+        let added = client.add_sheet("id", "new", credential()).await.unwrap();
+        assert_eq!(
+            client
+                .sheet_id_by_title("id", "new", credential())
+                .await
+                .unwrap(),
+            Some(added.properties.sheet_id.clone())
+        );
+
+        client
+            .delete_sheet("id", &added.properties.sheet_id, credential())
+            .await
+            .unwrap();
+        assert_eq!(fake.sheet_titles("id"), vec![String::from("Sheet1")]);
+
+        let err = client
+            .read("missing", "Sheet1", credential())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn grid_data() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.set_values("id", "Sheet1", vec![vec![json!("a")], vec![json!("b")]]);
+        fake.set_cell(
+            "id",
+            "Sheet1",
+            1,
+            0,
+            FakeCell {
+                value: json!("b"),
+                effective_format: None,
+                text_format_runs: Some(json!([{"startIndex": 0}])),
+            },
+        );
+
+        let spreadsheet: Value = fake
+            .client()
+            .get_spreadsheet("id", &["Sheet1!A1:A"], true, credential())
+            .await
+            .unwrap();
+        let rows = &spreadsheet["sheets"][0]["data"][0]["rowData"];
+        assert_eq!(rows.as_array().unwrap().len(), 2);
+        assert_eq!(rows[1]["values"][0]["formattedValue"], "b");
+        assert_eq!(rows[1]["values"][0]["textFormatRuns"][0]["startIndex"], 0);
+    }
+
+    #[tokio::test]
+    async fn updates_cells() {
+        use crate::sheet::{
+            CellData, ExtendedValue, GridCoordinate, RowData, TextFormat, TextFormatRun,
+            UpdateCells,
+        };
+
+        let fake = FakeSheets::start().await.unwrap();
+        fake.set_values("id", "Sheet1", vec![vec![json!("a"), json!("b")]]);
+        let client = fake.client();
+        let sheet_id = client
+            .sheet_id_by_title("id", "Sheet1", credential())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let italic = |italic| TextFormat {
+            italic: Some(italic),
+            strikethrough: None,
+        };
+        let cell = CellData {
+            user_entered_value: Some(ExtendedValue {
+                string_value: String::from("Dunes; The Ledge"),
+            }),
+            user_entered_format: None,
+            text_format_runs: vec![
+                TextFormatRun {
+                    start_index: 0,
+                    format: italic(false),
+                },
+                TextFormatRun {
+                    start_index: 7,
+                    format: italic(true),
+                },
+            ],
+        };
+        client
+            .update_cells(
+                "id",
+                vec![UpdateCells {
+                    start: GridCoordinate {
+                        sheet_id,
+                        row_index: 0,
+                        column_index: 1,
+                    },
+                    rows: vec![RowData { values: vec![cell] }],
+                    fields: String::from("userEnteredValue,textFormatRuns"),
+                }],
+                credential(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fake.values("id", "Sheet1").unwrap(),
+            vec![vec![json!("a"), json!("Dunes; The Ledge")]]
+        );
+        let spreadsheet: Value = client
+            .get_spreadsheet("id", &["Sheet1!B1"], true, credential())
+            .await
+            .unwrap();
+        let runs =
+            &spreadsheet["sheets"][0]["data"][0]["rowData"][0]["values"][0]["textFormatRuns"];
+        assert_eq!(runs[1]["startIndex"], 7);
+        assert_eq!(runs[1]["format"]["italic"], true);
+    }
+}
@@ -1,6 +1,22 @@
+pub mod auth;
+pub mod client;
 pub mod error;
+#[cfg(feature = "fake")]
+pub mod fake;
+pub mod range;
+pub mod retry;
 pub mod sheet;
 
+pub use auth::CredentialProvider;
+#[cfg(not(target_arch = "wasm32"))]
+pub use auth::RefreshToken;
+#[cfg(all(feature = "service-account", not(target_arch = "wasm32")))]
+pub use auth::ServiceAccount;
+pub use client::SheetsClient;
+pub use error::ErrorKind;
+pub use range::{A1Range, Column, ToA1};
+pub use retry::{set_retry_policy, RetryPolicy};
 pub use sheet::{
-    add_sheet, add_sheet_with_values, batch_update, read, read_batch, write_values_into_sheet,
+    add_sheet, add_sheet_with_values, batch_update, changed_ranges, clear_range, delete_sheet,
+    list_sheets, read, read_batch, sheet_id_by_title, update_cells, write_values_into_sheet,
 };
@@ -0,0 +1,478 @@
+//! A1 notation ranges.
+//!
+//! ```
+//! # use googlesheets::range::{A1Range, Column};
+//! let names = A1Range::column("3.28", Column::letter('F'), 3);
+//! assert_eq!(names.to_string(), "'3.28'!F3:F");
+//!
+//! let note = A1Range::cell("My sample", Column::letter('H'), 5);
+//! assert_eq!(note.to_string(), "'My sample'!H5");
+//! assert_eq!(note.url_encoded(), "'My%20sample'!H5");
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt::Display, str::FromStr};
+
+/// Sheet column, zero-based. Displayed as letters: A, B, ..., Z, AA, AB, ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Column(u32);
+
+impl Column {
+    pub const A: Column = Column(0);
+
+    pub const fn new(index: u32) -> Column {
+        Column(index)
+    }
+
+    /// Column of a single letter.
+    ///
+    /// # Panics
+    /// If `letter` is not an ASCII letter. In const context this fails the build.
+    pub const fn letter(letter: char) -> Column {
+        assert!(letter.is_ascii_alphabetic(), "Column letter must be A-Z");
+        Column(letter.to_ascii_uppercase() as u32 - 'A' as u32)
+    }
+
+    /// Parses letters like `F` or `AB`, case-insensitive.
+    pub fn from_letters(letters: &str) -> Option<Column> {
+        if letters.is_empty() || !letters.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return None;
+        }
+        letters
+            .to_ascii_uppercase()
+            .bytes()
+            .try_fold(0u32, |acc, b| {
+                acc.checked_mul(26)?.checked_add(u32::from(b - b'A') + 1)
+            })
+            .map(|number| Column(number - 1))
+    }
+
+    pub const fn index(self) -> u32 {
+        self.0
+    }
+
+    /// Column `offset` columns to the right, `None` on overflow.
+    pub const fn checked_add(self, offset: u32) -> Option<Column> {
+        match self.0.checked_add(offset) {
+            Some(index) => Some(Column(index)),
+            None => None,
+        }
+    }
+
+    /// Column `offset` columns to the left, `None` if it would be left of A.
+    pub const fn checked_sub(self, offset: u32) -> Option<Column> {
+        match self.0.checked_sub(offset) {
+            Some(index) => Some(Column(index)),
+            None => None,
+        }
+    }
+
+    pub fn letters(self) -> String {
+        let mut letters = vec![];
+        let mut number = u64::from(self.0) + 1;
+        while number > 0 {
+            let remainder = (number - 1) % 26;
+            letters.push(b'A' + remainder as u8);
+            number = (number - 1) / 26;
+        }
+        letters.reverse();
+        String::from_utf8(letters).unwrap_or_default()
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.letters())
+    }
+}
+
+impl FromStr for Column {
+    type Err = ParseA1Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Column::from_letters(s).ok_or_else(|| ParseA1Error(s.to_owned()))
+    }
+}
+
+impl Serialize for Column {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.letters())
+    }
+}
+
+impl<'de> Deserialize<'de> for Column {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Corner of a range. Missing column or row makes the range open in that direction:
+/// `F` is the whole column, `3` is the whole row. Rows are 1-based, like in the sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CellRef {
+    pub column: Option<Column>,
+    pub row: Option<u32>,
+}
+
+impl CellRef {
+    pub const fn new(column: Column, row: u32) -> CellRef {
+        CellRef {
+            column: Some(column),
+            row: Some(row),
+        }
+    }
+
+    pub const fn column(column: Column) -> CellRef {
+        CellRef {
+            column: Some(column),
+            row: None,
+        }
+    }
+
+    pub const fn row(row: u32) -> CellRef {
+        CellRef {
+            column: None,
+            row: Some(row),
+        }
+    }
+
+    fn parse(s: &str) -> Option<CellRef> {
+        let letters_len = s.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let (letters, digits) = s.split_at(letters_len);
+        // Columns end at ZZZ, longer words are sheet titles like `Sheet1`
+        if letters_len > 3 || (letters.is_empty() && digits.is_empty()) {
+            return None;
+        }
+
+        let column = match letters.is_empty() {
+            true => None,
+            false => Some(Column::from_letters(letters)?),
+        };
+        let row = match digits.is_empty() {
+            true => None,
+            false => Some(digits.parse::<u32>().ok().filter(|row| *row > 0)?),
+        };
+
+        Some(CellRef { column, row })
+    }
+}
+
+impl Display for CellRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(column) = self.column {
+            write!(f, "{column}")?;
+        }
+        if let Some(row) = self.row {
+            write!(f, "{row}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseA1Error(pub String);
+
+impl Display for ParseA1Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid A1 notation: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseA1Error {}
+
+/// Range in A1 notation. Without cells, refers to the whole sheet,
+/// without sheet title, refers to the first visible sheet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct A1Range {
+    pub sheet: Option<String>,
+    pub start: Option<CellRef>,
+    pub end: Option<CellRef>,
+}
+
+impl A1Range {
+    /// All cells of the sheet.
+    pub fn sheet(title: impl Into<String>) -> A1Range {
+        A1Range {
+            sheet: Some(title.into()),
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Single cell, row is 1-based.
+    pub fn cell(title: impl Into<String>, column: Column, row: u32) -> A1Range {
+        A1Range::sheet(title).with_start(CellRef::new(column, row))
+    }
+
+    /// Column from `start_row` down to the last row: `F3:F`.
+    pub fn column(title: impl Into<String>, column: Column, start_row: u32) -> A1Range {
+        A1Range::columns(title, column, column, start_row)
+    }
+
+    /// Columns from `start_row` down to the last row: `A3:Z`.
+    pub fn columns(
+        title: impl Into<String>,
+        start: Column,
+        end: Column,
+        start_row: u32,
+    ) -> A1Range {
+        A1Range::sheet(title)
+            .with_start(CellRef::new(start, start_row))
+            .with_end(CellRef::column(end))
+    }
+
+    #[must_use]
+    pub fn with_start(mut self, start: CellRef) -> A1Range {
+        self.start = Some(start);
+        self
+    }
+
+    #[must_use]
+    pub fn with_end(mut self, end: CellRef) -> A1Range {
+        self.end = Some(end);
+        self
+    }
+
+    /// Sheet title as it goes into A1 notation: quoted, unless it is a plain identifier.
+    pub fn quoted_sheet(&self) -> Option<String> {
+        self.sheet.as_deref().map(quote_sheet_title)
+    }
+
+    /// A1 notation, ready to be put into url path or query.
+    pub fn url_encoded(&self) -> String {
+        encode(&self.to_string())
+    }
+}
+
+impl Display for A1Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = match (self.start, self.end) {
+            (Some(start), Some(end)) => Some(format!("{start}:{end}")),
+            (Some(cell), None) | (None, Some(cell)) => Some(cell.to_string()),
+            (None, None) => None,
+        };
+        match (self.quoted_sheet(), cells) {
+            (Some(sheet), Some(cells)) => write!(f, "{sheet}!{cells}"),
+            (Some(sheet), None) => f.write_str(&sheet),
+            (None, Some(cells)) => f.write_str(&cells),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl FromStr for A1Range {
+    type Err = ParseA1Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseA1Error(s.to_owned());
+
+        let (sheet, cells) = match s.strip_prefix('\'') {
+            Some(quoted) => {
+                let (title, rest) = unquote(quoted).ok_or_else(err)?;
+                match rest.strip_prefix('!') {
+                    Some(cells) => (Some(title), cells),
+                    None if rest.is_empty() => (Some(title), ""),
+                    None => return Err(err()),
+                }
+            }
+            None => match s.rsplit_once('!') {
+                Some((title, cells)) => (Some(title.to_owned()), cells),
+                None if CellRef::parse(s.split(':').next().unwrap_or_default()).is_some() => {
+                    (None, s)
+                }
+                None => (Some(s.to_owned()), ""),
+            },
+        };
+
+        if cells.is_empty() {
+            return Ok(A1Range {
+                sheet,
+                start: None,
+                end: None,
+            });
+        }
+
+        let (start, end) = match cells.split_once(':') {
+            Some((start, end)) => (
+                CellRef::parse(start).ok_or_else(err)?,
+                Some(CellRef::parse(end).ok_or_else(err)?),
+            ),
+            None => (CellRef::parse(cells).ok_or_else(err)?, None),
+        };
+
+        Ok(A1Range {
+            sheet,
+            start: Some(start),
+            end,
+        })
+    }
+}
+
+impl Serialize for A1Range {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for A1Range {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Anything, that can be used as a range in sheet requests.
+/// Strings are taken as ready A1 notation, [`A1Range`] quotes sheet titles itself.
+pub trait ToA1 {
+    fn to_a1(&self) -> String;
+}
+
+impl ToA1 for A1Range {
+    fn to_a1(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToA1 for str {
+    fn to_a1(&self) -> String {
+        self.to_owned()
+    }
+}
+
+impl ToA1 for String {
+    fn to_a1(&self) -> String {
+        self.clone()
+    }
+}
+
+impl<T: ToA1 + ?Sized> ToA1 for &T {
+    fn to_a1(&self) -> String {
+        (**self).to_a1()
+    }
+}
+
+fn quote_sheet_title(title: &str) -> String {
+    let plain = title
+        .chars()
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && title
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        && CellRef::parse(title).is_none();
+
+    match plain {
+        true => title.to_owned(),
+        false => format!("'{}'", title.replace('\'', "''")),
+    }
+}
+
+/// Reads quoted title after the opening quote. Returns title and the rest after the closing quote.
+fn unquote(quoted: &str) -> Option<(String, &str)> {
+    let mut title = String::new();
+    let mut chars = quoted.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        if ch != '\'' {
+            title.push(ch);
+            continue;
+        }
+        if chars.next_if(|(_, next)| *next == '\'').is_some() {
+            title.push('\'');
+            continue;
+        }
+        return Some((title, &quoted[index + 1..]));
+    }
+    None
+}
+
+/// Percent-encodes everything, that is not safe in both url path and query.
+pub(crate) fn encode(a1: &str) -> String {
+    let mut encoded = String::with_capacity(a1.len());
+    for byte in a1.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b':'
+            | b'\''
+            | b'$'
+            | b'('
+            | b')'
+            | b'*'
+            | b','
+            | b';'
+            | b'@' => encoded.push(char::from(byte)),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_letters() {
+        assert_eq!(Column::A.letters(), "A");
+        assert_eq!(Column::letter('z').letters(), "Z");
+        assert_eq!(Column::letter('Z').checked_add(1).unwrap().letters(), "AA");
+        assert_eq!(Column::new(701).letters(), "ZZ");
+        assert_eq!(Column::new(702).letters(), "AAA");
+        assert_eq!(Column::from_letters("ab"), Some(Column::new(27)));
+        assert_eq!(Column::from_letters("A1"), None);
+        assert_eq!(
+            Column::letter('Q').checked_sub(Column::letter('F').index()),
+            Some(Column::new(11))
+        );
+        assert_eq!(Column::A.checked_sub(1), None);
+        assert_eq!(Column::new(u32::MAX).checked_add(1), None);
+        assert_eq!(Column::new(u32::MAX).letters(), "MWLQKWV");
+    }
+
+    #[test]
+    fn quotes_sheet_titles() {
+        assert_eq!(
+            A1Range::sheet("Cards_and_Hypotheses").to_string(),
+            "Cards_and_Hypotheses"
+        );
+        assert_eq!(A1Range::sheet("It's mine").to_string(), "'It''s mine'");
+        assert_eq!(A1Range::cell("A1", Column::A, 1).to_string(), "'A1'!A1");
+        assert_eq!(
+            A1Range::columns("Cards_and_Hypotheses", Column::A, Column::letter('Z'), 3).to_string(),
+            "Cards_and_Hypotheses!A3:Z"
+        );
+    }
+
+    #[test]
+    fn parses_and_roundtrips() {
+        for a1 in [
+            "'3.28'!F3:F",
+            "'It''s mine'!H5",
+            "science!A1:D5",
+            "B2:C",
+            "Sheet1",
+            "'a b'",
+        ] {
+            assert_eq!(a1.parse::<A1Range>().unwrap().to_string(), a1);
+        }
+
+        let range: A1Range = "3.28!Q3:Q".parse().unwrap();
+        assert_eq!(range, A1Range::column("3.28", Column::letter('Q'), 3));
+        assert!("'unclosed!A1".parse::<A1Range>().is_err());
+        assert!("Sheet!A0".parse::<A1Range>().is_err());
+    }
+
+    #[test]
+    fn url_encoding() {
+        let range = A1Range::cell("Drops & weights #2", Column::A, 1);
+        assert_eq!(range.url_encoded(), "'Drops%20%26%20weights%20%232'!A1");
+        assert_eq!(encode("a+b/c?"), "a%2Bb%2Fc%3F");
+    }
+}
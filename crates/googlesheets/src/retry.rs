@@ -0,0 +1,217 @@
+//! Retries of Sheets API requests.
+//!
+//! Sheets answers 429 and 503 quite often under quota pressure, for example when several ranges
+//! are read concurrently. Such requests are repeated with exponential backoff,
+//! following <https://developers.google.com/sheets/api/limits#exponential>.
+//!
+//! In wasm requests are sent once: there is no timer and no clock to back off with in the browser.
+
+use crate::error::{Error, GoogleError, GoogleErrorResponse};
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{sync::RwLock, time::Duration};
+
+static RETRY_POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::DEFAULT);
+
+/// Exponential backoff settings for retrying requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of retries after the first attempt. 0 disables retrying.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: u32,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT: RetryPolicy = RetryPolicy {
+        max_retries: 5,
+        initial_backoff: Duration::from_millis(500),
+        max_backoff: Duration::from_secs(32),
+        multiplier: 2,
+    };
+
+    pub const NONE: RetryPolicy = RetryPolicy {
+        max_retries: 0,
+        ..RetryPolicy::DEFAULT
+    };
+
+    /// Delay before retry number `attempt`(starting from 0), without jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.multiplier
+            .checked_pow(attempt)
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::DEFAULT
+    }
+}
+
+/// Sets retry policy for all subsequent sheet requests of clients without own policy.
+pub fn set_retry_policy(policy: RetryPolicy) {
+    *RETRY_POLICY
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+}
+
+pub fn retry_policy() -> RetryPolicy {
+    *RETRY_POLICY
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Statuses, that are worth repeating the request for.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Statuses, that are worth repeating a non-idempotent request for. Only quota errors
+/// guarantee the request was not applied, 5xx may come after the change is made.
+pub fn is_retryable_status_non_idempotent(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Sends request, retrying on quota and availability errors. Statuses >= 400 become [`Error::GoogleError`].
+pub(crate) async fn send(request: RequestBuilder, policy: RetryPolicy) -> Result<Response, Error> {
+    send_with(request, policy, true).await
+}
+
+/// Sends request, that must not be applied twice, like adding a sheet.
+/// Retries only quota errors and failed connections.
+pub(crate) async fn send_non_idempotent(
+    request: RequestBuilder,
+    policy: RetryPolicy,
+) -> Result<Response, Error> {
+    send_with(request, policy, false).await
+}
+
+async fn send_with(
+    request: RequestBuilder,
+    policy: RetryPolicy,
+    idempotent: bool,
+) -> Result<Response, Error> {
+    #[cfg(target_arch = "wasm32")]
+    let policy = RetryPolicy::NONE;
+    let is_retryable = match idempotent {
+        true => is_retryable_status,
+        false => is_retryable_status_non_idempotent,
+    };
+    let mut attempt = 0;
+
+    loop {
+        // Requests with streaming bodies can not be cloned, those are sent only once.
+        let retry_request = match attempt < policy.max_retries {
+            true => request.try_clone(),
+            false => None,
+        };
+        let Some(current) = retry_request else {
+            return check_status(request.send().await?).await;
+        };
+
+        let delay = match current.send().await {
+            Ok(response) if is_retryable(response.status()) => {
+                retry_after(&response).unwrap_or_else(|| jittered(policy.backoff(attempt)))
+            }
+            Ok(response) => return check_status(response).await,
+            Err(err) if err.is_connect() || (idempotent && err.is_timeout()) => {
+                jittered(policy.backoff(attempt))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        tracing::debug!("Sheets request failed, retry {} in {delay:?}", attempt + 1);
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+async fn check_status(response: Response) -> Result<Response, Error> {
+    let status = response.status();
+    if status.as_u16() < 400 {
+        return Ok(response);
+    }
+
+    let text = response.text().await?;
+    let error = match serde_json::from_str::<GoogleErrorResponse>(&text) {
+        Ok(err_response) => err_response.error,
+        // Proxies and overloaded frontends answer with html or plain text.
+        Err(_) => GoogleError {
+            code: u32::from(status.as_u16()),
+            message: text,
+            status: status.canonical_reason().unwrap_or_default().to_owned(),
+        },
+    };
+    Err(error.into())
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Adds up to a second of jitter, so concurrent requests do not retry in lockstep.
+#[cfg(not(target_arch = "wasm32"))]
+fn jittered(delay: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    delay + Duration::from_millis(u64::from(nanos % 1000))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn jittered(delay: Duration) -> Duration {
+    delay
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await;
+}
+
+/// Not reached, requests are not retried in wasm.
+#[cfg(target_arch = "wasm32")]
+async fn sleep(_delay: Duration) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = RetryPolicy::DEFAULT;
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(10), Duration::from_secs(32));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(32));
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+
+        assert!(is_retryable_status_non_idempotent(
+            StatusCode::TOO_MANY_REQUESTS
+        ));
+        assert!(!is_retryable_status_non_idempotent(
+            StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(!is_retryable_status_non_idempotent(StatusCode::BAD_GATEWAY));
+    }
+}
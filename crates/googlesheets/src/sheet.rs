@@ -1,15 +1,11 @@
-use crate::error::{Error, GoogleErrorResponse};
-use reqwest::Client;
+use crate::auth::CredentialProvider;
+use crate::client::SheetsClient;
+use crate::error::Error;
+use crate::range::{A1Range, Column, ToA1};
+use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::Value;
 use std::fmt::{Debug, Display};
-use std::sync::LazyLock;
-
-static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
-    Client::builder()
-        .build()
-        .expect("Failed to create reqwest client")
-});
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Credential {
@@ -17,6 +13,18 @@ pub enum Credential {
     ApiKey(String),
 }
 
+impl Credential {
+    /// Bearer header for access token, `key` query parameter for api key.
+    pub(crate) fn authorize(self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            Credential::AccessToken(token) => {
+                request.header("Authorization", format!("Bearer {token}"))
+            }
+            Credential::ApiKey(api_key) => request.query(&[("key", api_key)]),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReadBatchResponse {
@@ -24,75 +32,45 @@ pub struct ReadBatchResponse {
     pub value_ranges: Vec<ValueRange>,
 }
 
-pub async fn read_batch(
+pub async fn read_batch<R: ToA1>(
     spreadsheet_id: &str,
-    ranges: &[&str],
-    credential: Credential,
+    ranges: &[R],
+    credential: impl CredentialProvider,
 ) -> Result<ReadBatchResponse, Error> {
-    let formatted_ranges = ranges
-        .iter()
-        .map(|range| format!("ranges={range}"))
-        .collect::<Vec<String>>()
-        .join("&");
-
-    let response = match credential {
-        Credential::AccessToken(token) => {
-            let url =
-        format!("https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values:batchGet?{formatted_ranges}");
-            HTTP_CLIENT
-                .get(url)
-                .header("Authorization", format!("Bearer {token}"))
-                .send()
-                .await?
-        }
-        Credential::ApiKey(api_key) => {
-            let url =
-        format!("https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values:batchGet?{formatted_ranges}&key={api_key}");
-            HTTP_CLIENT.get(url).send().await?
-        }
-    };
-
-    if response.status().as_u16() >= 400 {
-        let err_response: GoogleErrorResponse = response.json().await?;
-        Err(err_response.error.into())
-    } else {
-        let value: ReadBatchResponse = response.json().await?;
-        Ok(value)
-    }
+    SheetsClient::global()
+        .read_batch(spreadsheet_id, ranges, credential)
+        .await
 }
 
 pub async fn read(
     spreadsheet_id: &str,
-    range: &str,
-    credential: Credential,
+    range: impl ToA1,
+    credential: impl CredentialProvider,
 ) -> Result<ValueRange, Error> {
-    let response = match credential {
-        Credential::AccessToken(token) => {
-            let url = format!(
-                "https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values/{range}"
-            );
-
-            HTTP_CLIENT
-                .get(url)
-                .header("Authorization", format!("Bearer {token}"))
-                .send()
-                .await?
-        }
-        Credential::ApiKey(api_key) => {
-            let url = format!(
-                "https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values/{range}?key={api_key}"
-            );
+    SheetsClient::global()
+        .read(spreadsheet_id, range, credential)
+        .await
+}
 
-            HTTP_CLIENT.get(url).send().await?
-        }
-    };
+/// How values are rendered in read responses.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ValueRenderOption {
+    /// Values as shown in the sheet, numbers come as strings: `"0.35"`.
+    #[default]
+    FormattedValue,
+    /// Numbers come as numbers, the way they were written.
+    UnformattedValue,
+    Formula,
+}
 
-    if response.status().as_u16() >= 400 {
-        let err_response: GoogleErrorResponse = response.json().await?;
-        Err(err_response.error.into())
-    } else {
-        let value_range: ValueRange = response.json().await?;
-        Ok(value_range)
+impl ValueRenderOption {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ValueRenderOption::FormattedValue => "FORMATTED_VALUE",
+            ValueRenderOption::UnformattedValue => "UNFORMATTED_VALUE",
+            ValueRenderOption::Formula => "FORMULA",
+        }
     }
 }
 
@@ -110,6 +88,7 @@ pub struct ValueRange {
     #[serde(rename = "majorDimension")]
     pub dimension: Dimension,
     pub range: String,
+    #[serde(default)]
     pub values: Vec<Vec<Value>>,
 }
 
@@ -123,51 +102,36 @@ impl ValueRange {
     }
 }
 
-#[tracing::instrument(skip(data, token))]
 pub async fn batch_update(
     spreadsheet_id: &str,
     data: Vec<ValueRange>,
-    token: &str,
+    credential: impl CredentialProvider,
 ) -> Result<Value, Error> {
-    let response = HTTP_CLIENT
-        .post(format!(
-            "https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values:batchUpdate
-"
-        ))
-        .header("Authorization", format!("Bearer {token}"))
-        .body(
-            json!({
-                "valueInputOption": "RAW",
-                "data": data
-            })
-            .to_string(),
-        )
-        .send()
-        .await?;
-
-    if response.status().as_u16() >= 400 {
-        let err_response: GoogleErrorResponse = response.json().await?;
-        Err(err_response.error.into())
-    } else {
-        let write_values: Value = response.json().await?;
-        Ok(write_values)
-    }
+    SheetsClient::global()
+        .batch_update(spreadsheet_id, data, credential)
+        .await
+}
+
+/// Writes values and text format runs of cells, see [`UpdateCells`].
+pub async fn update_cells(
+    spreadsheet_id: &str,
+    requests: Vec<UpdateCells>,
+    credential: impl CredentialProvider,
+) -> Result<(), Error> {
+    SheetsClient::global()
+        .update_cells(spreadsheet_id, requests, credential)
+        .await
 }
 
-#[tracing::instrument(skip(values))]
 pub async fn add_sheet_with_values<T: Serialize + Debug>(
     spreadsheet_id: &str,
     title: &str,
     values: Vec<Vec<Value>>,
-    token: &str,
+    credential: impl CredentialProvider,
 ) -> Result<SheetUrl, Error> {
-    let add_sheet_data = add_sheet(spreadsheet_id, title, token).await?;
-    let _ = write_values_into_sheet(spreadsheet_id, title, token, values).await?;
-
-    Ok(SheetUrl::create(
-        spreadsheet_id,
-        add_sheet_data.properties.sheet_id,
-    ))
+    SheetsClient::global()
+        .add_sheet_with_values::<T>(spreadsheet_id, title, values, credential)
+        .await
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -183,71 +147,206 @@ pub struct WriteValuesResponse {
 pub async fn write_values_into_sheet(
     spreadsheet_id: &str,
     title: &str,
-    token: &str,
+    credential: impl CredentialProvider,
     values: Vec<Vec<Value>>,
 ) -> Result<WriteValuesResponse, Error> {
-    let url = format!("https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}/values/{title}?valueInputOption=RAW
-");
-
-    let body = serde_json::to_string(&json!({
-      "range": title,
-      "majorDimension": "ROWS",
-      "values": values
-    }))?;
-
-    let response = HTTP_CLIENT
-        .put(url)
-        .header("Authorization", format!("Bearer {token}"))
-        .body(body)
-        .send()
-        .await?;
-
-    if response.status().as_u16() >= 400 {
-        let err_response: GoogleErrorResponse = response.json().await?;
-        Err(err_response.error.into())
-    } else {
-        let write_values: WriteValuesResponse = response.json().await?;
-        Ok(write_values)
+    SheetsClient::global()
+        .write_values_into_sheet(spreadsheet_id, title, credential, values)
+        .await
+}
+
+pub async fn add_sheet(
+    spreadsheet_id: &str,
+    title: &str,
+    credential: impl CredentialProvider,
+) -> Result<AddSheet, Error> {
+    SheetsClient::global()
+        .add_sheet(spreadsheet_id, title, credential)
+        .await
+}
+
+/// Lists properties of every sheet(tab) in the spreadsheet.
+pub async fn list_sheets(
+    spreadsheet_id: &str,
+    credential: impl CredentialProvider,
+) -> Result<Vec<AddSheetProperties>, Error> {
+    SheetsClient::global()
+        .list_sheets(spreadsheet_id, credential)
+        .await
+}
+
+/// Finds the id of the sheet(tab) with given title.
+pub async fn sheet_id_by_title(
+    spreadsheet_id: &str,
+    title: &str,
+    credential: impl CredentialProvider,
+) -> Result<Option<SheetId>, Error> {
+    SheetsClient::global()
+        .sheet_id_by_title(spreadsheet_id, title, credential)
+        .await
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearValuesResponse {
+    pub spreadsheet_id: String,
+    pub cleared_range: Option<String>,
+}
+
+/// Clears values from the range, keeping formatting.
+pub async fn clear_range(
+    spreadsheet_id: &str,
+    range: impl ToA1,
+    credential: impl CredentialProvider,
+) -> Result<ClearValuesResponse, Error> {
+    SheetsClient::global()
+        .clear_range(spreadsheet_id, range, credential)
+        .await
+}
+
+pub async fn delete_sheet(
+    spreadsheet_id: &str,
+    sheet_id: &SheetId,
+    credential: impl CredentialProvider,
+) -> Result<(), Error> {
+    SheetsClient::global()
+        .delete_sheet(spreadsheet_id, sheet_id, credential)
+        .await
+}
+
+/// Compares two tables of sheet values and returns only the rows that differ,
+/// as value ranges ready for [`batch_update`]. Consecutive changed rows are grouped into one range.
+/// Cells present in `old` but missing in `new` are overwritten with empty strings.
+/// Numbers are compared with single precision and equal to their formatted strings,
+/// so values read back from the sheet match the values they were written from.
+pub fn changed_ranges(title: &str, old: &[Vec<Value>], new: &[Vec<Value>]) -> Vec<ValueRange> {
+    let empty = Value::String(String::new());
+    let height = old.len().max(new.len());
+
+    let mut ranges: Vec<ValueRange> = vec![];
+    let mut pending: Option<(usize, Vec<Vec<Value>>)> = None;
+
+    for index in 0..height {
+        let old_row = old.get(index).map(Vec::as_slice).unwrap_or_default();
+        let new_row = new.get(index).map(Vec::as_slice).unwrap_or_default();
+        let width = old_row.len().max(new_row.len());
+
+        let cell = |row: &[Value], column: usize| -> Value {
+            match row.get(column) {
+                Some(Value::Null) | None => empty.clone(),
+                Some(value) => value.clone(),
+            }
+        };
+        let row: Vec<Value> = (0..width).map(|column| cell(new_row, column)).collect();
+        let changed = (0..width).any(|column| !same_cell(&cell(old_row, column), &row[column]));
+
+        if changed {
+            pending.get_or_insert_with(|| (index, vec![])).1.push(row);
+        } else if let Some((start, values)) = pending.take() {
+            ranges.push(ValueRange::new(
+                Dimension::Rows,
+                row_start(title, start),
+                values,
+            ));
+        }
+    }
+
+    if let Some((start, values)) = pending {
+        ranges.push(ValueRange::new(
+            Dimension::Rows,
+            row_start(title, start),
+            values,
+        ));
     }
+
+    ranges
 }
 
-pub async fn add_sheet(spreadsheet_id: &str, title: &str, token: &str) -> Result<AddSheet, Error> {
-    let response = HTTP_CLIENT
-        .post(format!(
-            "https://sheets.googleapis.com/v4/spreadsheets/{spreadsheet_id}:batchUpdate"
-        ))
-        .header("Authorization", format!("Bearer {}", { token }))
-        .body(
-            json!({
-               "requests":[
-                  {
-                     "addSheet":{
-                        "properties":{
-                           "title": title
-                        }
-                     }
-                  }
-               ]
-            })
-            .to_string(),
-        )
-        .send()
-        .await?;
-
-    if response.status().as_u16() >= 400 {
-        let err_response: GoogleErrorResponse = response.json().await?;
-        Err(err_response.error.into())
-    } else {
-        let response: BatchResponse = response.json().await?;
-        let add_sheet = response.replies[0].add_sheet.clone();
-
-        Ok(add_sheet)
+fn same_cell(old: &Value, new: &Value) -> bool {
+    let number = |value: &Value| match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    match (old, new) {
+        (Value::Number(_), _) | (_, Value::Number(_)) => match (number(old), number(new)) {
+            (Some(old), Some(new)) => old as f32 == new as f32,
+            _ => false,
+        },
+        _ => old == new,
     }
 }
 
+/// First cell of zero-based row.
+fn row_start(title: &str, index: usize) -> String {
+    let row = u32::try_from(index + 1).unwrap_or(u32::MAX);
+    A1Range::cell(title, Column::A, row).to_string()
+}
+
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn changed_ranges_groups_consecutive_rows() {
+        let old = vec![
+            vec![json!("name"), json!("amount")],
+            vec![json!("Rain of Chaos"), json!(1)],
+            vec![json!("The Doctor"), json!(1)],
+            vec![json!("The Fool"), json!(2)],
+        ];
+        let new = vec![
+            vec![json!("name"), json!("amount")],
+            vec![json!("Rain of Chaos"), json!(5)],
+            vec![json!("The Doctor"), json!(2)],
+            vec![json!("The Fool"), json!(2)],
+            vec![json!("The Nurse"), json!(1)],
+        ];
+
+        let ranges = changed_ranges("science", &old, &new);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].range, "science!A2");
+        assert_eq!(ranges[0].values.len(), 2);
+        assert_eq!(ranges[1].range, "science!A5");
+    }
+
+    #[test]
+    fn changed_ranges_clears_removed_cells() {
+        let old = vec![
+            vec![json!("name"), json!("amount")],
+            vec![json!("The Doctor"), json!(1)],
+        ];
+        let new = vec![vec![json!("name"), json!("amount")]];
+
+        let ranges = changed_ranges("science", &old, &new);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].values, vec![vec![json!(""), json!("")]]);
+    }
+
+    #[test]
+    fn changed_ranges_matches_read_back_numbers() {
+        let new = vec![
+            vec![json!("name"), json!("amount"), json!("weight")],
+            vec![json!("The Doctor"), json!(2), Value::from(0.35f32)],
+        ];
+        let formatted = vec![
+            vec![json!("name"), json!("amount"), json!("weight")],
+            vec![json!("The Doctor"), json!("2"), json!("0.35")],
+        ];
+        let unformatted = vec![
+            vec![json!("name"), json!("amount"), json!("weight")],
+            vec![json!("The Doctor"), json!(2), json!(0.35)],
+        ];
+
+        assert!(changed_ranges("science", &formatted, &new).is_empty());
+        assert!(changed_ranges("science", &unformatted, &new).is_empty());
+        let changed = vec![
+            vec![json!("name"), json!("amount"), json!("weight")],
+            vec![json!("The Doctor"), json!("3"), json!(0.35)],
+        ];
+        assert_eq!(changed_ranges("science", &changed, &new).len(), 1);
+    }
 
     //     #[tokio::test]
     //     async fn batch_update_test() {
@@ -320,13 +419,19 @@ pub struct BatchReply {
     pub add_sheet: AddSheet,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub(crate) struct SpreadsheetSheets {
+    #[serde(default)]
+    pub(crate) sheets: Vec<AddSheet>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct AddSheet {
     pub properties: AddSheetProperties,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct AddSheetProperties {
     pub sheet_id: SheetId,
     pub title: String,
@@ -360,6 +465,72 @@ pub struct GridProperties {
     pub row_count: u32,
 }
 
+/// `updateCells` request of spreadsheets.batchUpdate. Unlike values, keeps rich text of the cells.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCells {
+    /// Top left cell of `rows`.
+    pub start: GridCoordinate,
+    pub rows: Vec<RowData>,
+    /// Field mask of the written cell data, for example `userEnteredValue,textFormatRuns`.
+    pub fields: String,
+}
+
+/// Zero-based cell of the sheet.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GridCoordinate {
+    pub sheet_id: SheetId,
+    pub row_index: u32,
+    pub column_index: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct RowData {
+    pub values: Vec<CellData>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CellData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_entered_value: Option<ExtendedValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_entered_format: Option<CellFormat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text_format_runs: Vec<TextFormatRun>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendedValue {
+    pub string_value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CellFormat {
+    pub text_format: TextFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFormat {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+}
+
+/// Format of the text from `start_index` (in UTF-16 code units) to the next run.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFormatRun {
+    #[serde(default)]
+    pub start_index: u32,
+    pub format: TextFormat,
+}
+
 // #[tauri::command]
 // pub async fn read_sheet() -> Result<Value, Error> {
 //     let client = &HTTP_CLIENT;
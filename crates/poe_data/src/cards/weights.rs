@@ -22,50 +22,37 @@
 //! | 3.23   | `3.23!D3:D` | `3.23!P3:P` |
 
 use anyhow::{Context, Result};
-use divi::sample::{Input, Sample};
-use googlesheets::sheet::Credential;
+use divi::sample::{Sample, SheetColumns, SheetInput};
+use googlesheets::{A1Range, Column, SheetsClient, sheet::Credential};
 use std::collections::HashMap;
 
 const SPREADSHEET_ID: &str = "1PmGES_e1on6K7O5ghHuoorEjruAVb7dQ5m7PGrW7t80";
 
 struct LeagueRange {
     version: String,
-    names_range: String,
-    weights_range: String,
+    names_range: A1Range,
+    weights_range: A1Range,
+}
+
+impl LeagueRange {
+    /// Names and weights columns of the league tab, both start at row 3.
+    fn new(version: &str, names_column: char, weights_column: char) -> Self {
+        LeagueRange {
+            version: version.into(),
+            names_range: A1Range::column(version, Column::letter(names_column), 3),
+            weights_range: A1Range::column(version, Column::letter(weights_column), 3),
+        }
+    }
 }
 
 fn league_ranges() -> [LeagueRange; 6] {
     [
-        LeagueRange {
-            version: "3.28".into(),
-            names_range: "3.28!F3:F".into(),
-            weights_range: "3.28!Q3:Q".into(),
-        },
-        LeagueRange {
-            version: "3.27".into(),
-            names_range: "3.27!H3:H".into(),
-            weights_range: "3.27!S3:S".into(),
-        },
-        LeagueRange {
-            version: "3.26".into(),
-            names_range: "3.26!H3:H".into(),
-            weights_range: "3.26!S3:S".into(),
-        },
-        LeagueRange {
-            version: "3.25".into(),
-            names_range: "3.25!F3:F".into(),
-            weights_range: "3.25!Q3:Q".into(),
-        },
-        LeagueRange {
-            version: "3.24".into(),
-            names_range: "3.24!D3:D".into(),
-            weights_range: "3.24!O3:O".into(),
-        },
-        LeagueRange {
-            version: "3.23".into(),
-            names_range: "3.23!D3:D".into(),
-            weights_range: "3.23!P3:P".into(),
-        },
+        LeagueRange::new("3.28", 'F', 'Q'),
+        LeagueRange::new("3.27", 'H', 'S'),
+        LeagueRange::new("3.26", 'H', 'S'),
+        LeagueRange::new("3.25", 'F', 'Q'),
+        LeagueRange::new("3.24", 'D', 'O'),
+        LeagueRange::new("3.23", 'D', 'P'),
     ]
 }
 
@@ -88,28 +75,36 @@ struct LeagueData {
     sample: Option<Sample>,
 }
 
-async fn fetch_league_data(api_key: String, lr: &LeagueRange, idx: usize) -> Result<LeagueData> {
+async fn fetch_league_data(
+    client: &SheetsClient,
+    api_key: String,
+    lr: &LeagueRange,
+    idx: usize,
+) -> Result<LeagueData> {
     eprintln!("  fetching {}...", lr.version);
-    let resp = googlesheets::sheet::read_batch(
-        SPREADSHEET_ID,
-        &[&lr.names_range, &lr.weights_range],
-        Credential::ApiKey(api_key),
-    )
-    .await
-    .map_err(|e| anyhow::anyhow!("Failed to read Google Sheets range {}: {}", lr.version, e))?;
+    let resp = client
+        .read_batch(
+            SPREADSHEET_ID,
+            &[&lr.names_range, &lr.weights_range],
+            &Credential::ApiKey(api_key),
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read Google Sheets range {}: {}", lr.version, e))?;
 
     let (sample, row_count) = if resp.value_ranges.len() >= 2 {
-        let row_count = resp.value_ranges[0]
-            .values
-            .iter()
-            .filter(|row| {
-                row.first()
-                    .and_then(|v| v.as_str())
-                    .is_some_and(|s| !s.trim().is_empty())
-            })
-            .count();
-        let input = Input::try_from(resp)
-            .map_err(|e| anyhow::anyhow!("Failed to parse sheet data for {}: {}", lr.version, e))?;
+        let input =
+            SheetInput::from_value_ranges(&resp.value_ranges, &SheetColumns::by_index(0, 1))
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to parse sheet data for {}: {}", lr.version, e)
+                })?;
+        if !input.skipped.is_empty() {
+            eprintln!(
+                "  {}: skipped {} rows without name or amount",
+                lr.version,
+                input.skipped.len()
+            );
+        }
+        let row_count = input.pairs.len();
         let s = Sample::create(input, None)
             .map_err(|e| anyhow::anyhow!("Failed to compute weights for {}: {}", lr.version, e))?;
         (Some(s), row_count)
@@ -132,6 +127,11 @@ async fn fetch_league_data(api_key: String, lr: &LeagueRange, idx: usize) -> Res
 pub async fn fetch() -> Result<Weights> {
     dotenv::dotenv().ok();
     let api_key = std::env::var("GOOGLE_API_KEY").context("GOOGLE_API_KEY not set")?;
+    fetch_from(SheetsClient::global(), &api_key).await
+}
+
+/// Same as [`fetch`], but reads through given client instead of the real Sheets API.
+pub async fn fetch_from(client: &SheetsClient, api_key: &str) -> Result<Weights> {
     let ranges = league_ranges();
     let versions: Vec<String> = ranges.iter().map(|r| r.version.clone()).collect();
 
@@ -139,7 +139,7 @@ pub async fn fetch() -> Result<Weights> {
         ranges
             .iter()
             .enumerate()
-            .map(|(idx, lr)| fetch_league_data(api_key.clone(), lr, idx)),
+            .map(|(idx, lr)| fetch_league_data(client, api_key.to_owned(), lr, idx)),
     )
     .await?;
 
@@ -178,3 +178,70 @@ pub async fn fetch() -> Result<Weights> {
         total_cards,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use googlesheets::fake::FakeSheets;
+    use serde_json::{Value, json};
+
+    /// Rows of the league tab: names and amounts in given columns, starting at row 3.
+    fn league_rows(
+        names_column: usize,
+        amounts_column: usize,
+        rows: &[(&str, &str)],
+    ) -> Vec<Vec<Value>> {
+        let mut values = vec![vec![], vec![]];
+        for (name, amount) in rows {
+            let mut row = vec![json!(""); amounts_column + 1];
+            row[names_column] = json!(name);
+            row[amounts_column] = json!(amount);
+            values.push(row);
+        }
+        values
+    }
+
+    #[tokio::test]
+    async fn fetches_weights_from_fake_sheets() {
+        let fake = FakeSheets::start().await.unwrap();
+        let titles = league_ranges().map(|range| range.version);
+        fake.create_spreadsheet(
+            SPREADSHEET_ID,
+            &titles.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        fake.set_values(
+            SPREADSHEET_ID,
+            "3.28",
+            league_rows(
+                5,
+                16,
+                &[
+                    ("Rain of Chaos", "100"),
+                    ("The Doctor", "30"),
+                    ("The Nurse", "n/a"),
+                ],
+            ),
+        );
+        fake.set_values(
+            SPREADSHEET_ID,
+            "3.27",
+            league_rows(7, 18, &[("Rain of Chaos", "50")]),
+        );
+
+        let weights = fetch_from(&fake.client(), "key").await.unwrap();
+        assert_eq!(
+            weights.versions,
+            ["3.28", "3.27", "3.26", "3.25", "3.24", "3.23"]
+        );
+        assert_eq!(weights.total_cards, 130);
+
+        let rain_of_chaos = &weights.per_card["Rain of Chaos"];
+        assert!(rain_of_chaos["3.28"] > 0.0);
+        assert_eq!(rain_of_chaos["3.28"], rain_of_chaos["3.27"]);
+        let doctor = &weights.per_card["The Doctor"];
+        assert!(doctor["3.28"] > 0.0 && doctor["3.28"] < rain_of_chaos["3.28"]);
+        assert_eq!(doctor["3.27"], 0.0);
+        // The row without amount is skipped
+        assert_eq!(weights.per_card["The Nurse"]["3.28"], 0.0);
+    }
+}
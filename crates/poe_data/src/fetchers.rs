@@ -22,14 +22,15 @@
 //!
 //! All fetchers read game files from the configured [`GameFiles`] source
 //! (Steam install, GGG CDN or GGPK file), opened once per process via
-//! [`open_game_data`] and shared across pieces.
+//! [`open_game_data`] and shared across pieces. Data of a pinned CDN patch is
+//! cached next to the default files, e.g. `acts.cdn-3.29.0.json`.
 
 use crate::{GameFiles, act, cards, log, mapbosses, maps, open_game_data};
 use card_element::DivinationCardElementData;
 use divcord::poe_data::{PoeData, act::ActArea, cards::CardsData, mapbosses::MapBoss, maps::Map};
 use divi::TradeLeague;
-use fs_cache_fetcher::{Config, DataFetcher, Stale};
-use std::fmt::Display;
+use fs_cache_fetcher::{Config, DataFetcher, FsStorage, ServeStale, Stale};
+use std::{fmt::Display, sync::Arc};
 
 /// Error type for dump-backed fetchers.
 #[derive(Debug)]
@@ -75,6 +76,21 @@ impl From<tokio::task::JoinError> for Error {
     }
 }
 
+/// Pieces are shared in one process: [`PoeDataFetcher`] and [`CardElementsFetcher`]
+/// both load cards, concurrent loads wait for one another.
+const MEMO_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// League of card prices.
+const CARDS_LEAGUE: TradeLeague = TradeLeague::Allflame;
+
+/// Cache variant of the game files source: CDN patches get their own files.
+fn source_variant(source: &GameFiles) -> Option<String> {
+    match source {
+        GameFiles::Cdn(patch) => Some(format!("cdn-{patch}")),
+        GameFiles::Steam(_) | GameFiles::Ggpk(_) => None,
+    }
+}
+
 /// Campaign act areas (`acts.json`).
 pub struct ActsFetcher {
     config: Config,
@@ -86,8 +102,11 @@ impl Default for ActsFetcher {
         Self {
             config: Config {
                 save: true,
-                filename: "acts.json",
+                filename: "acts.json".to_owned(),
                 stale: Stale::ReloadEveryTime,
+                storage: Arc::new(FsStorage::project_data()),
+                memo_ttl: Some(MEMO_TTL),
+                ..Config::default()
             },
             source: GameFiles::default(),
         }
@@ -127,6 +146,14 @@ impl DataFetcher for ActsFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.config
     }
+
+    fn variant(&self) -> Option<String> {
+        source_variant(&self.source)
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.source.to_string())
+    }
 }
 
 /// Atlas maps (`maps.json`).
@@ -140,8 +167,11 @@ impl Default for MapsFetcher {
         Self {
             config: Config {
                 save: true,
-                filename: "maps.json",
+                filename: "maps.json".to_owned(),
                 stale: Stale::ReloadEveryTime,
+                storage: Arc::new(FsStorage::project_data()),
+                memo_ttl: Some(MEMO_TTL),
+                ..Config::default()
             },
             source: GameFiles::default(),
         }
@@ -179,6 +209,14 @@ impl DataFetcher for MapsFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.config
     }
+
+    fn variant(&self) -> Option<String> {
+        source_variant(&self.source)
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.source.to_string())
+    }
 }
 
 /// Map bosses (`mapBosses.json`).
@@ -192,8 +230,11 @@ impl Default for MapBossesFetcher {
         Self {
             config: Config {
                 save: true,
-                filename: "mapBosses.json",
+                filename: "mapBosses.json".to_owned(),
                 stale: Stale::ReloadEveryTime,
+                storage: Arc::new(FsStorage::project_data()),
+                memo_ttl: Some(MEMO_TTL),
+                ..Config::default()
             },
             source: GameFiles::default(),
         }
@@ -231,6 +272,14 @@ impl DataFetcher for MapBossesFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.config
     }
+
+    fn variant(&self) -> Option<String> {
+        source_variant(&self.source)
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.source.to_string())
+    }
 }
 
 /// Divination cards with community weights, prices, and league info
@@ -245,8 +294,12 @@ impl Default for CardsFetcher {
         Self {
             config: Config {
                 save: true,
-                filename: "cards.json",
+                filename: "cards.json".to_owned(),
                 stale: Stale::After(std::time::Duration::from_secs(1)),
+                storage: Arc::new(FsStorage::project_data()),
+                serve_stale: ServeStale::OnError,
+                memo_ttl: Some(MEMO_TTL),
+                ..Config::default()
             },
             source: GameFiles::default(),
         }
@@ -265,7 +318,7 @@ impl DataFetcher for CardsFetcher {
     type Error = Error;
 
     async fn fetch(&self) -> Result<CardsData, Error> {
-        Ok(cards::extract_cards(&self.source, TradeLeague::Allflame).await?)
+        Ok(cards::extract_cards(&self.source, CARDS_LEAGUE).await?)
     }
 
     fn config(&self) -> &Config {
@@ -275,6 +328,14 @@ impl DataFetcher for CardsFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.config
     }
+
+    fn variant(&self) -> Option<String> {
+        source_variant(&self.source)
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(format!("{}, {CARDS_LEAGUE}", self.source))
+    }
 }
 
 /// Enriched card element data (`cardElementData.json`), built from the
@@ -288,8 +349,12 @@ impl Default for CardElementsFetcher {
         Self {
             config: Config {
                 save: true,
-                filename: "cardElementData.json",
+                filename: "cardElementData.json".to_owned(),
                 stale: Stale::After(std::time::Duration::from_secs(1)),
+                storage: Arc::new(FsStorage::project_data()),
+                serve_stale: ServeStale::OnError,
+                memo_ttl: Some(MEMO_TTL),
+                ..Config::default()
             },
         }
     }
@@ -313,6 +378,10 @@ impl DataFetcher for CardElementsFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.config
     }
+
+    fn source(&self) -> Option<String> {
+        CardsFetcher::default().source()
+    }
 }
 
 /// Full `PoeData` composition (`poeData.json`), assembled from the four piece
@@ -331,8 +400,11 @@ impl Default for PoeDataFetcher {
         Self {
             config: Config {
                 save: true,
-                filename: "poeData.json",
+                filename: "poeData.json".to_owned(),
                 stale: Stale::ReloadEveryTime,
+                storage: Arc::new(FsStorage::project_data()),
+                memo_ttl: Some(MEMO_TTL),
+                ..Config::default()
             },
             source: GameFiles::default(),
             acts: Default::default(),
@@ -419,6 +491,7 @@ impl DataFetcher for PoeDataFetcher {
             cards,
             maps,
             mapbosses,
+            ..Default::default()
         })
     }
 
@@ -429,4 +502,12 @@ impl DataFetcher for PoeDataFetcher {
     fn config_mut(&mut self) -> &mut Config {
         &mut self.config
     }
+
+    fn variant(&self) -> Option<String> {
+        source_variant(&self.source)
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.source.to_string())
+    }
 }
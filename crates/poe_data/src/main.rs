@@ -208,6 +208,7 @@ async fn main() -> Result<()> {
                 cards: cards_output,
                 maps,
                 mapbosses: bosses,
+                ..Default::default()
             };
             std::fs::write(
                 output.join("poeData.json"),
@@ -3,8 +3,14 @@ use crate::{
     google::{AccessTokenStorage, Persist},
 };
 use chrono::Utc;
-use divi::{sample::Sample, League};
-use googlesheets::sheet::{Credential, Dimension, ReadBatchResponse, SheetUrl, ValueRange};
+use divi::{
+    sample::{Sample, SyncMode},
+    League,
+};
+use googlesheets::{
+    sheet::{Credential, Dimension, ReadBatchResponse, SheetUrl, ValueRange, ValueRenderOption},
+    A1Range, Column, CredentialProvider, SheetsClient,
+};
 use serde_json::json;
 use tracing::debug;
 
@@ -17,27 +23,67 @@ pub async fn new_sheet_with_sample(
     league: League,
     preferences: Option<divi::sample::TablePreferences>,
 ) -> Result<SheetUrl, Error> {
-    let token = AccessTokenStorage::new().get().unwrap();
-    let add_sheet_response = googlesheets::add_sheet(spreadsheet_id, title, &token).await?;
+    let credential = Credential::AccessToken(AccessTokenStorage::new().get().unwrap());
+    export_new_sheet(
+        SheetsClient::global(),
+        spreadsheet_id,
+        title,
+        sample,
+        league,
+        preferences,
+        &credential,
+    )
+    .await
+}
+
+/// Writes sample into an existing sheet, changing only the cells that differ.
+/// Creates the sheet, if there is no sheet with this title yet.
+#[tauri::command]
+#[tracing::instrument(skip(sample))]
+pub async fn update_sheet_with_sample(
+    spreadsheet_id: &str,
+    title: &str,
+    sample: Sample,
+    league: League,
+    preferences: Option<divi::sample::TablePreferences>,
+    mode: SyncMode,
+) -> Result<SheetUrl, Error> {
+    let credential = Credential::AccessToken(AccessTokenStorage::new().get().unwrap());
+    export_into_sheet(
+        SheetsClient::global(),
+        spreadsheet_id,
+        title,
+        sample,
+        league,
+        preferences,
+        mode,
+        &credential,
+    )
+    .await
+}
+
+/// Adds new sheet and writes sample into it. Backs [`new_sheet_with_sample`].
+pub async fn export_new_sheet(
+    client: &SheetsClient,
+    spreadsheet_id: &str,
+    title: &str,
+    sample: Sample,
+    league: League,
+    preferences: Option<divi::sample::TablePreferences>,
+    credential: impl CredentialProvider,
+) -> Result<SheetUrl, Error> {
+    let add_sheet_response = client.add_sheet(spreadsheet_id, title, &credential).await?;
 
     let sample_values = ValueRange {
         dimension: Dimension::Rows,
-        range: title.to_string(),
+        range: A1Range::sheet(title).to_string(),
         values: sample.into_serde_values(preferences),
     };
+    let note = league_note(title, &league, table_width(&sample_values.values));
 
-    let aside_values = ValueRange {
-        dimension: Dimension::Rows,
-        range: format!("{title}!H5"),
-        values: vec![vec![json!(format!(
-            "{} {league} League",
-            Utc::now().date_naive().format("%-d %b, %C%y")
-        ))]],
-    };
-
-    let batch_response =
-        googlesheets::batch_update(spreadsheet_id, vec![sample_values, aside_values], &token)
-            .await?;
+    let batch_response = client
+        .batch_update(spreadsheet_id, vec![sample_values, note], &credential)
+        .await?;
 
     debug!("{batch_response}");
 
@@ -47,6 +93,108 @@ pub async fn new_sheet_with_sample(
     ))
 }
 
+/// Syncs sample into the sheet. Backs [`update_sheet_with_sample`].
+#[allow(clippy::too_many_arguments)]
+pub async fn export_into_sheet(
+    client: &SheetsClient,
+    spreadsheet_id: &str,
+    title: &str,
+    sample: Sample,
+    league: League,
+    preferences: Option<divi::sample::TablePreferences>,
+    mode: SyncMode,
+    credential: impl CredentialProvider,
+) -> Result<SheetUrl, Error> {
+    let sheet_id = match client
+        .sheet_id_by_title(spreadsheet_id, title, &credential)
+        .await?
+    {
+        Some(sheet_id) => sheet_id,
+        None => {
+            client
+                .add_sheet(spreadsheet_id, title, &credential)
+                .await?
+                .properties
+                .sheet_id
+        }
+    };
+
+    let (mut changed, synced_width) = changed_sample_ranges(
+        client,
+        spreadsheet_id,
+        title,
+        sample,
+        preferences,
+        mode,
+        &credential,
+    )
+    .await?;
+    changed.push(league_note(title, &league, synced_width));
+
+    let batch_response = client
+        .batch_update(spreadsheet_id, changed, &credential)
+        .await?;
+
+    debug!("{batch_response}");
+
+    Ok(SheetUrl::create(spreadsheet_id, sheet_id))
+}
+
+/// Data ranges of the sheet, that differ from the synced sample, and the width of the synced table.
+/// Reads unformatted values, so numbers compare with the numbers of the sample.
+async fn changed_sample_ranges(
+    client: &SheetsClient,
+    spreadsheet_id: &str,
+    title: &str,
+    sample: Sample,
+    preferences: Option<divi::sample::TablePreferences>,
+    mode: SyncMode,
+    credential: impl CredentialProvider,
+) -> Result<(Vec<ValueRange>, u32), Error> {
+    let existing = client
+        .read_rendered(
+            spreadsheet_id,
+            A1Range::sheet(title),
+            ValueRenderOption::UnformattedValue,
+            &credential,
+        )
+        .await?
+        .values;
+
+    // Only the table under the header is synced, cells to the right of it are left as is.
+    let header_width = existing.first().map(Vec::len).unwrap_or_default();
+    let existing: Vec<_> = existing
+        .into_iter()
+        .map(|mut row| {
+            row.truncate(header_width);
+            row
+        })
+        .collect();
+
+    let values = sample.sync_serde_values(&existing, preferences, mode);
+    let changed = googlesheets::changed_ranges(title, &existing, &values);
+    let synced_width = table_width(&existing).max(table_width(&values));
+    Ok((changed, synced_width))
+}
+
+fn table_width(values: &[Vec<serde_json::Value>]) -> u32 {
+    values.iter().map(Vec::len).max().unwrap_or_default() as u32
+}
+
+/// Date and league note, placed aside of the table: in H5 or, for wider tables,
+/// one empty column after the last column of the table, so it never overwrites synced cells.
+fn league_note(title: &str, league: &League, table_width: u32) -> ValueRange {
+    let column = Column::letter('H').max(Column::new(table_width + 1));
+    ValueRange {
+        dimension: Dimension::Rows,
+        range: A1Range::cell(title, column, 5).to_string(),
+        values: vec![vec![json!(format!(
+            "{} {league} League",
+            Utc::now().date_naive().format("%-d %b, %C%y")
+        ))]],
+    }
+}
+
 #[tauri::command]
 #[tracing::instrument]
 pub async fn read_batch(
@@ -75,3 +223,66 @@ pub async fn read_sheet(spreadsheet_id: &str, range: &str) -> Result<ValueRange,
 
     Ok(value_range)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use divi::sample::Input;
+    use googlesheets::fake::FakeSheets;
+
+    #[tokio::test]
+    async fn exports_sample_to_fake_sheets() {
+        let fake = FakeSheets::start().await.unwrap();
+        fake.create_spreadsheet("spreadsheet", &["Sheet1"]);
+        let client = fake.client();
+        let credential = Credential::AccessToken(String::from("token"));
+        let sample = Sample::create(
+            Input::Csv(String::from("name,amount\nRain of Chaos,30\nThe Doctor,1")),
+            None,
+        )
+        .unwrap();
+
+        export_new_sheet(
+            &client,
+            "spreadsheet",
+            "My sample",
+            sample.clone(),
+            League::Standard,
+            None,
+            &credential,
+        )
+        .await
+        .unwrap();
+        let exported = fake.values("spreadsheet", "My sample").unwrap();
+        assert!(exported
+            .iter()
+            .any(|row| row.first().is_some_and(|name| name == "The Doctor")));
+
+        let (changed, _) = changed_sample_ranges(
+            &client,
+            "spreadsheet",
+            "My sample",
+            sample.clone(),
+            None,
+            SyncMode::Merge,
+            &credential,
+        )
+        .await
+        .unwrap();
+        assert!(changed.is_empty());
+
+        export_into_sheet(
+            &client,
+            "spreadsheet",
+            "My sample",
+            sample,
+            League::Standard,
+            None,
+            SyncMode::Merge,
+            &credential,
+        )
+        .await
+        .unwrap();
+        assert_eq!(fake.values("spreadsheet", "My sample").unwrap(), exported);
+    }
+}
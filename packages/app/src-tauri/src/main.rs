@@ -52,6 +52,7 @@ async fn main() {
             google::auth::google_logout,
             google::auth::google_identity,
             google::sheets::new_sheet_with_sample,
+            google::sheets::update_sheet_with_sample,
             google::sheets::read_sheet,
             google::sheets::read_batch,
             poe::stash::sample_from_tab_with_items,
@@ -29,6 +29,14 @@ export interface Commands {
     league: League;
     preferences: Preferences;
   }) => string;
+  update_sheet_with_sample: (args: {
+    spreadsheetId: string;
+    title: string;
+    sample: DivinationCardsSample;
+    league: League;
+    preferences: Preferences;
+    mode: "merge" | "replace";
+  }) => string;
   google_logout: () => void;
   google_identity: () => GoogleIdentity;
   google_auth: () => void;